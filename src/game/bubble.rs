@@ -0,0 +1,127 @@
+//! Bubbles. A bubble swallows the first gas particle it touches and carries it
+//! along as it floats up. It pops when it touches something sharp or hot, or
+//! when its short life runs out, and lets go of whatever gas it was holding.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::SimulationOrder;
+use super::decay::Age;
+use super::elements::{DiffusionRule, Element, ElementType};
+use super::heat::Temperature;
+use super::particle::{Particle, neighbours};
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (fit_bubbles, pop_bubbles, trap_gases)
+            .chain()
+            .in_set(SimulationOrder::Bubble),
+    );
+}
+
+/// Elements sharp enough to pop a bubble.
+const SHARP: [ElementType; 3] = [ElementType::Glass, ElementType::Metal, ElementType::Stone];
+
+/// Bubbles pop when they, or anything touching them, get this hot.
+const POP_TEMPERATURE: f32 = 150.0;
+
+/// A bubble, and the gas trapped inside it with its temperature.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Bubble {
+    trapped: Option<(ElementType, f32)>,
+}
+
+/// Starts particles that just became a bubble off empty, and forgets the gas
+/// in particles that stopped being one.
+fn fit_bubbles(
+    mut commands: Commands,
+    particles: Query<(Entity, &ElementType, Has<Bubble>), Changed<ElementType>>,
+) {
+    for (entity, element_type, is_bubble) in &particles {
+        match (*element_type == ElementType::Bubble, is_bubble) {
+            (true, false) => {
+                commands.entity(entity).try_insert(Bubble::default());
+            }
+            (false, true) => {
+                commands.entity(entity).try_remove::<Bubble>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pops bubbles that touch something sharp or hot, or are about to burst on
+/// their own, releasing the gas inside where they were.
+fn pop_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    bubbles: Query<(Entity, &Transform, &Temperature, &Bubble, Option<&Age>)>,
+    particles: Query<(&ElementType, &Temperature)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, transform, temperature, bubble, age) in &bubbles {
+        let position = transform.translation.xy();
+        // Bubbles that run out of time disappear later this tick, so they have
+        // to let go of their gas now.
+        let bursting = age.is_some_and(|age| age.elapsed + dt >= age.lifetime);
+        let popped = bursting
+            || temperature.0 >= POP_TEMPERATURE
+            || neighbours(&spatial_query, entity, position)
+                .into_iter()
+                .filter_map(|other| particles.get(other).ok())
+                .any(|(element_type, temperature)| {
+                    SHARP.contains(element_type) || temperature.0 >= POP_TEMPERATURE
+                });
+        if !popped {
+            continue;
+        }
+
+        commands.entity(entity).try_despawn();
+        if let Some((element_type, temperature)) = bubble.trapped {
+            commands.spawn((
+                Particle::new(element_type, position).with_temperature(temperature),
+                ScreenWrap,
+            ));
+        }
+    }
+}
+
+/// Lets each empty bubble swallow a gas particle it touches.
+fn trap_gases(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut bubbles: Query<(Entity, &Transform, &mut Bubble)>,
+    gases: Query<(&ElementType, &Element, &Temperature), Without<Bubble>>,
+) {
+    let mut bubbles: Vec<_> = bubbles
+        .iter_mut()
+        .filter(|(.., bubble)| bubble.trapped.is_none())
+        .collect();
+    bubbles.sort_by_key(|(entity, ..)| *entity);
+
+    let mut trapped = HashSet::new();
+    for (entity, transform, mut bubble) in bubbles {
+        let gas = neighbours(&spatial_query, entity, transform.translation.xy())
+            .into_iter()
+            .find(|other| {
+                !trapped.contains(other)
+                    && gases.get(*other).is_ok_and(|(element_type, element, _)| {
+                        element.diffusion_rule == DiffusionRule::Diffuse
+                            && *element_type != ElementType::Cloud
+                    })
+            });
+        let Some(gas) = gas else {
+            continue;
+        };
+        let Ok((element_type, _, temperature)) = gases.get(gas) else {
+            continue;
+        };
+        trapped.insert(gas);
+        commands.entity(gas).try_despawn();
+        bubble.trapped = Some((*element_type, temperature.0));
+    }
+}
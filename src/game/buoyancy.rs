@@ -0,0 +1,178 @@
+//! Buoyancy between particles of different densities. Particles surrounded by
+//! a denser fluid are pushed up, and heavier particles resting on a lighter
+//! fluid sink into it by trading places, so oil ends up floating on water and
+//! sand settles through it. Gases rising through the air is handled by their
+//! [`Motion`](super::diffusion::Motion).
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::{HashMap as Map, HashSet};
+
+use super::SimulationOrder;
+use super::diffusion::wander_gases;
+use super::elements::{DiffusionRule, Element, ElementType};
+use super::rng::SimulationRng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (apply_buoyancy, displace_lighter_fluids)
+            .chain()
+            .after(wander_gases)
+            .in_set(SimulationOrder::Buoyancy),
+    );
+}
+
+/// Buoyancy is capped at this many times gravity, so a wisp of gas caught in
+/// water rises quickly instead of shooting out.
+const MAX_BUOYANCY: f32 = 3.0;
+
+/// Chance per tick that a heavier particle resting on a lighter fluid trades
+/// places with it. Lower values make layers separate more gently.
+const DISPLACEMENT_CHANCE: f64 = 0.1;
+
+/// What buoyancy needs to know about a particle.
+#[derive(Clone, Copy)]
+struct Body {
+    element_type: ElementType,
+    density: f32,
+    fluid: bool,
+    dynamic: bool,
+    position: Vec2,
+}
+
+fn is_fluid(element: &Element) -> bool {
+    matches!(
+        element.diffusion_rule,
+        DiffusionRule::Fill | DiffusionRule::Diffuse
+    )
+}
+
+/// Pushes particles up by the weight of the fluid they displace. Only fluids of
+/// other elements count, otherwise a pool of water would float on itself. A
+/// particle is only as submerged as the share of its neighbours that are such
+/// fluids, so one sitting on the bottom of a pool gets less lift than one
+/// floating in it.
+fn apply_buoyancy(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut particles: Query<(
+        Entity,
+        &ElementType,
+        &Element,
+        &RigidBody,
+        &CollidingEntities,
+        &mut LinearVelocity,
+    )>,
+) {
+    let dt = time.delta_secs();
+    let fluids: Map<Entity, (ElementType, f32)> = particles
+        .iter()
+        .filter(|(_, _, element, ..)| is_fluid(element))
+        .map(|(entity, element_type, element, ..)| (entity, (*element_type, element.density)))
+        .collect();
+
+    for (_, element_type, element, rigid_body, colliding, mut velocity) in &mut particles {
+        if !rigid_body.is_dynamic() || colliding.is_empty() {
+            continue;
+        }
+        let surrounding: Vec<f32> = colliding
+            .iter()
+            .filter_map(|other| fluids.get(other))
+            .filter(|(other_type, _)| other_type != element_type)
+            .map(|(_, density)| *density)
+            .collect();
+        if surrounding.is_empty() {
+            continue;
+        }
+        let medium_density = surrounding.iter().sum::<f32>() / surrounding.len() as f32;
+        let submerged = surrounding.len() as f32 / colliding.len() as f32;
+        let lift =
+            (submerged * medium_density / element.density.max(f32::EPSILON)).min(MAX_BUOYANCY);
+        velocity.0 -= gravity.0 * lift * dt;
+    }
+}
+
+/// Lets a heavier particle sink into a lighter fluid below it by swapping their
+/// positions. Each particle trades places at most once per tick.
+fn displace_lighter_fluids(
+    mut rng: ResMut<SimulationRng>,
+    mut particles: Query<(
+        Entity,
+        &ElementType,
+        &Element,
+        &RigidBody,
+        &CollidingEntities,
+        &mut Transform,
+    )>,
+) {
+    let bodies: Map<Entity, Body> = particles
+        .iter()
+        .map(
+            |(entity, element_type, element, rigid_body, _, transform)| {
+                let body = Body {
+                    element_type: *element_type,
+                    density: element.density,
+                    fluid: is_fluid(element),
+                    dynamic: rigid_body.is_dynamic(),
+                    position: transform.translation.xy(),
+                };
+                (entity, body)
+            },
+        )
+        .collect();
+
+    let mut candidates: Vec<(Entity, Vec<Entity>)> = particles
+        .iter()
+        .map(|(entity, .., colliding, _)| {
+            let mut touching: Vec<Entity> = colliding.iter().copied().collect();
+            touching.sort();
+            (entity, touching)
+        })
+        .collect();
+    candidates.sort_by_key(|(entity, _)| *entity);
+
+    let mut swapped = HashSet::new();
+    let mut swaps = Vec::new();
+    for (upper, touching) in candidates {
+        let Some(heavy) = bodies.get(&upper) else {
+            continue;
+        };
+        if !heavy.dynamic || swapped.contains(&upper) {
+            continue;
+        }
+        for lower in touching {
+            let Some(light) = bodies.get(&lower) else {
+                continue;
+            };
+            let offset = heavy.position - light.position;
+            let is_below = offset.y > 0.0 && offset.x.abs() < offset.y;
+            if !light.fluid
+                || !light.dynamic
+                || light.element_type == heavy.element_type
+                || light.density >= heavy.density
+                || !is_below
+                || swapped.contains(&lower)
+            {
+                continue;
+            }
+            if rng.random_bool(DISPLACEMENT_CHANCE) {
+                swapped.insert(upper);
+                swapped.insert(lower);
+                swaps.push((upper, lower));
+                break;
+            }
+        }
+    }
+
+    for (a, b) in swaps {
+        let Ok([(.., mut transform_a), (.., mut transform_b)]) = particles.get_many_mut([a, b])
+        else {
+            continue;
+        };
+        let position_a = transform_a.translation;
+        transform_a.translation = transform_b.translation;
+        transform_b.translation = position_a;
+    }
+}
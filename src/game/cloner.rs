@@ -0,0 +1,120 @@
+//! Clones. A clone latches onto the element of the first particle that touches
+//! it, then keeps spawning copies of that element next to itself for as long
+//! as there is room and the [`ParticleBudget`] allows.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::particle::{Particle, ParticleBudget, neighbours};
+use super::rng::SimulationRng;
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CloneRate>();
+    app.add_systems(
+        FixedUpdate,
+        (latch_clones, emit_clones)
+            .chain()
+            .in_set(SimulationOrder::Cloner),
+    );
+}
+
+/// Where a clone can put a copy, relative to itself.
+const CLONE_OFFSETS: [Vec2; 8] = [
+    Vec2::new(-1.0, -1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(1.0, -1.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 1.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(1.0, 1.0),
+];
+
+/// How many copies each clone spawns per second.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct CloneRate(pub f32);
+
+impl Default for CloneRate {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+/// The element a clone copies, and how long until it spawns the next copy.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct CloneSource {
+    pub element_type: ElementType,
+    seconds_until_next: f32,
+}
+
+/// Makes clones copy the first thing that touches them.
+fn latch_clones(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    clones: Query<(Entity, &ElementType, &Transform), Without<CloneSource>>,
+    element_types: Query<&ElementType>,
+) {
+    for (entity, element_type, transform) in &clones {
+        if *element_type != ElementType::Clone {
+            continue;
+        }
+        let touched = neighbours(&spatial_query, entity, transform.translation.xy())
+            .into_iter()
+            .filter_map(|other| element_types.get(other).ok())
+            .find(|other| **other != ElementType::Clone);
+        if let Some(element_type) = touched {
+            commands.entity(entity).try_insert(CloneSource {
+                element_type: *element_type,
+                seconds_until_next: 0.0,
+            });
+        }
+    }
+}
+
+/// Spawns copies next to clones, in an empty spot picked at random.
+fn emit_clones(
+    mut commands: Commands,
+    time: Res<Time>,
+    rate: Res<CloneRate>,
+    budget: Res<ParticleBudget>,
+    mut rng: ResMut<SimulationRng>,
+    spatial_query: SpatialQuery,
+    mut clones: Query<(Entity, &Transform, &mut CloneSource)>,
+    particles: Query<(), With<ElementType>>,
+) {
+    let dt = time.delta_secs();
+    let mut ready: Vec<(Entity, Vec2, ElementType)> = Vec::new();
+    for (entity, transform, mut source) in &mut clones {
+        source.seconds_until_next -= dt;
+        if source.seconds_until_next <= 0.0 {
+            source.seconds_until_next += 1.0 / rate.0.max(f32::EPSILON);
+            ready.push((entity, transform.translation.xy(), source.element_type));
+        }
+    }
+    ready.sort_by_key(|(entity, ..)| *entity);
+
+    let mut count = particles.iter().count();
+    for (_, position, element_type) in ready {
+        if count >= budget.0 {
+            return;
+        }
+        let mut offsets = CLONE_OFFSETS;
+        offsets.shuffle(&mut rng.0);
+        let free_spot = offsets
+            .into_iter()
+            .map(|offset| position + offset)
+            .find(|spot| {
+                spatial_query
+                    .point_intersections(*spot, &SpatialQueryFilter::default())
+                    .is_empty()
+            });
+        if let Some(spot) = free_spot {
+            commands.spawn((Particle::new(element_type, spot), ScreenWrap));
+            count += 1;
+        }
+    }
+}
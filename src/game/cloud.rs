@@ -0,0 +1,118 @@
+//! Clouds. A cloud soaks up any steam that drifts into it and rains the water
+//! back down a drop at a time. Water collected by the top of a big cloud
+//! trickles down through it to rain out of the bottom.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::particle::{Particle, neighbours};
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (fit_clouds, gather_steam, rain)
+            .chain()
+            .in_set(SimulationOrder::Cloud),
+    );
+}
+
+/// Seconds between drops of rain from each cloud particle.
+const RAIN_INTERVAL: f32 = 0.2;
+
+/// A cloud particle, and the water it holds.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Cloud {
+    water: u32,
+    seconds_until_rain: f32,
+}
+
+/// Starts particles that just became cloud off empty, and forgets the water of
+/// particles that stopped being cloud.
+fn fit_clouds(
+    mut commands: Commands,
+    particles: Query<(Entity, &ElementType, Has<Cloud>), Changed<ElementType>>,
+) {
+    for (entity, element_type, is_cloud) in &particles {
+        match (*element_type == ElementType::Cloud, is_cloud) {
+            (true, false) => {
+                commands.entity(entity).try_insert(Cloud::default());
+            }
+            (false, true) => {
+                commands.entity(entity).try_remove::<Cloud>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Soaks up steam touching a cloud.
+fn gather_steam(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut clouds: Query<(Entity, &Transform, &mut Cloud)>,
+    element_types: Query<&ElementType>,
+) {
+    let mut clouds: Vec<_> = clouds.iter_mut().collect();
+    clouds.sort_by_key(|(entity, ..)| *entity);
+
+    let mut gathered = HashSet::new();
+    for (entity, transform, mut cloud) in clouds {
+        for other in neighbours(&spatial_query, entity, transform.translation.xy()) {
+            let is_steam = element_types
+                .get(other)
+                .is_ok_and(|element_type| *element_type == ElementType::Steam);
+            if is_steam && gathered.insert(other) {
+                commands.entity(other).try_despawn();
+                cloud.water += 1;
+            }
+        }
+    }
+}
+
+/// Drops water from clouds with nothing underneath, and passes it down to the
+/// cloud below otherwise.
+fn rain(
+    mut commands: Commands,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut clouds: Query<(Entity, &Transform, &mut Cloud)>,
+) {
+    let dt = time.delta_secs();
+    let mut trickles: Vec<(Entity, Entity)> = Vec::new();
+    for (entity, transform, mut cloud) in &mut clouds {
+        cloud.seconds_until_rain -= dt;
+        if cloud.water == 0 || cloud.seconds_until_rain > 0.0 {
+            continue;
+        }
+        cloud.seconds_until_rain = RAIN_INTERVAL;
+
+        let below = transform.translation.xy() - Vec2::Y;
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+        match spatial_query
+            .point_intersections(below, &filter)
+            .into_iter()
+            .min()
+        {
+            None => {
+                cloud.water -= 1;
+                commands.spawn((Particle::new(ElementType::Water, below), ScreenWrap));
+            }
+            Some(other) => trickles.push((entity, other)),
+        }
+    }
+
+    trickles.sort();
+    for (from, to) in trickles {
+        let Ok([(.., mut from), (.., mut to)]) = clouds.get_many_mut([from, to]) else {
+            continue;
+        };
+        if from.water > 0 {
+            from.water -= 1;
+            to.water += 1;
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Creatures. Ants and birds are particles like any other, so they burn, drown
+//! and get blown up through the usual element rules, but they also steer
+//! themselves around the sandbox.
+//!
+//! Ants walk along whatever they stand on, climb small steps, dig through
+//! powder and carry the grains off to drop somewhere else. Birds fly in flocks,
+//! keep away from anything hot and stop to perch on walls.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashSet;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::heat::Temperature;
+use super::particle::Particle;
+use super::rng::SimulationRng;
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (fit_creatures, walk_ants, fly_birds)
+            .chain()
+            .in_set(SimulationOrder::Creature),
+    );
+}
+
+/// Grains an ant can dig through and carry.
+const DIGGABLE: [ElementType; 3] = [ElementType::Powder, ElementType::Sand, ElementType::Ash];
+
+/// How fast ants walk.
+const ANT_SPEED: f32 = 4.0;
+
+/// How fast ants climb up a step in front of them.
+const ANT_CLIMB_SPEED: f32 = 6.0;
+
+/// Chance per tick that an ant drops the grain it carries.
+const ANT_DROP_CHANCE: f64 = 0.02;
+
+/// Slowest and fastest a flying bird goes.
+const BIRD_SPEED: (f32, f32) = (4.0, 12.0);
+
+/// How far a bird looks for flockmates.
+const FLOCK_RADIUS: f32 = 12.0;
+
+/// How strongly a bird steers towards the middle of its flock, matches its
+/// flockmates' heading and keeps its distance from them.
+const COHESION: f32 = 0.5;
+const ALIGNMENT: f32 = 1.0;
+const SEPARATION: f32 = 8.0;
+
+/// How far away birds notice something hot and how hot it has to be.
+const DANGER_RADIUS: f32 = 10.0;
+const DANGER_TEMPERATURE: f32 = 150.0;
+
+/// How hard birds steer away from danger.
+const FLEE: f32 = 40.0;
+
+/// Chance per tick that a bird flying over a wall lands on it, and how many
+/// seconds it stays.
+const PERCH_CHANCE: f64 = 0.05;
+const PERCH_SECONDS: (f32, f32) = (1.0, 4.0);
+
+/// An ant, the way it is facing, and the grain it is carrying.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Ant {
+    heading: f32,
+    carrying: Option<ElementType>,
+}
+
+/// A bird, and how long it stays perched if it has landed.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Bird {
+    perched_seconds: f32,
+}
+
+/// Gives particles that just became an ant or a bird their behavior, and
+/// takes it away from particles that stopped being one.
+fn fit_creatures(
+    mut commands: Commands,
+    mut rng: ResMut<SimulationRng>,
+    particles: Query<(Entity, &ElementType, Has<Ant>, Has<Bird>), Changed<ElementType>>,
+) {
+    let mut particles: Vec<_> = particles.iter().collect();
+    particles.sort_by_key(|(entity, ..)| *entity);
+
+    for (entity, element_type, is_ant, is_bird) in particles {
+        let mut entity = commands.entity(entity);
+        match element_type {
+            ElementType::Ant if !is_ant => {
+                let heading = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+                entity.try_insert(Ant {
+                    heading,
+                    carrying: None,
+                });
+            }
+            ElementType::Bird if !is_bird => {
+                entity.try_insert(Bird::default());
+            }
+            ElementType::Ant | ElementType::Bird => {}
+            _ => {
+                entity.try_remove::<(Ant, Bird)>();
+            }
+        }
+    }
+}
+
+/// The particle at `point`, other than `entity` itself, if there is one.
+fn occupied(spatial_query: &SpatialQuery, entity: Entity, point: Vec2) -> Option<Entity> {
+    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+    spatial_query
+        .point_intersections(point, &filter)
+        .into_iter()
+        .min()
+}
+
+/// Walks ants along the ground. An ant digs through a grain in its way if its
+/// jaws are free, climbs over a step and turns around at a wall.
+fn walk_ants(
+    mut commands: Commands,
+    mut rng: ResMut<SimulationRng>,
+    spatial_query: SpatialQuery,
+    mut ants: Query<(Entity, &Transform, &mut Ant, &mut LinearVelocity)>,
+    element_types: Query<&ElementType>,
+) {
+    let mut ants: Vec<_> = ants.iter_mut().collect();
+    ants.sort_by_key(|(entity, ..)| *entity);
+
+    let mut dug = HashSet::new();
+    for (entity, transform, mut ant, mut velocity) in ants {
+        let position = transform.translation.xy();
+        if occupied(&spatial_query, entity, position - Vec2::Y).is_none() {
+            // Falling.
+            continue;
+        }
+
+        let ahead = position + Vec2::X * ant.heading;
+        match occupied(&spatial_query, entity, ahead) {
+            None => velocity.x = ant.heading * ANT_SPEED,
+            Some(blocker) => {
+                let diggable = element_types
+                    .get(blocker)
+                    .ok()
+                    .filter(|element_type| DIGGABLE.contains(element_type));
+                if let (Some(grain), None) = (diggable, ant.carrying) {
+                    if dug.insert(blocker) {
+                        commands.entity(blocker).try_despawn();
+                        ant.carrying = Some(*grain);
+                    }
+                    velocity.x = ant.heading * ANT_SPEED;
+                } else if occupied(&spatial_query, entity, ahead + Vec2::Y).is_none() {
+                    velocity.0 = Vec2::new(ant.heading * ANT_SPEED, ANT_CLIMB_SPEED);
+                } else {
+                    ant.heading = -ant.heading;
+                    velocity.x = 0.0;
+                }
+            }
+        }
+
+        let Some(grain) = ant.carrying else {
+            continue;
+        };
+        if !rng.random_bool(ANT_DROP_CHANCE) {
+            continue;
+        }
+        let behind = position - Vec2::X * ant.heading;
+        if occupied(&spatial_query, entity, behind).is_none() {
+            commands.spawn((Particle::new(grain, behind), ScreenWrap));
+            ant.carrying = None;
+        }
+    }
+}
+
+/// Steers birds with their flock, away from anything hot, and down onto walls
+/// to rest.
+fn fly_birds(
+    time: Res<Time>,
+    mut rng: ResMut<SimulationRng>,
+    spatial_query: SpatialQuery,
+    mut birds: Query<(Entity, &Transform, &mut Bird, &mut LinearVelocity)>,
+    particles: Query<(&ElementType, &Transform, Option<&Temperature>)>,
+) {
+    let dt = time.delta_secs();
+    let flock: Vec<(Entity, Vec2, Vec2)> = birds
+        .iter()
+        .map(|(entity, transform, _, velocity)| (entity, transform.translation.xy(), velocity.0))
+        .collect();
+    let mut birds: Vec<_> = birds.iter_mut().collect();
+    birds.sort_by_key(|(entity, ..)| *entity);
+
+    for (entity, transform, mut bird, mut velocity) in birds {
+        if bird.perched_seconds > 0.0 {
+            bird.perched_seconds -= dt;
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+        let position = transform.translation.xy();
+
+        let below = occupied(&spatial_query, entity, position - Vec2::Y);
+        let over_wall = below
+            .and_then(|below| particles.get(below).ok())
+            .is_some_and(|(element_type, ..)| *element_type == ElementType::Wall);
+        if over_wall && rng.random_bool(PERCH_CHANCE) {
+            bird.perched_seconds = rng.random_range(PERCH_SECONDS.0..PERCH_SECONDS.1);
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+
+        let mut steering = Vec2::ZERO;
+        let flockmates: Vec<&(Entity, Vec2, Vec2)> = flock
+            .iter()
+            .filter(|(other, other_position, _)| {
+                *other != entity && other_position.distance(position) < FLOCK_RADIUS
+            })
+            .collect();
+        if !flockmates.is_empty() {
+            let count = flockmates.len() as f32;
+            let center = flockmates.iter().map(|(_, p, _)| *p).sum::<Vec2>() / count;
+            let heading = flockmates.iter().map(|(_, _, v)| *v).sum::<Vec2>() / count;
+            steering += (center - position) * COHESION;
+            steering += (heading - velocity.0) * ALIGNMENT;
+            for (_, other_position, _) in &flockmates {
+                let away = position - *other_position;
+                steering += away / away.length_squared().max(0.01) * SEPARATION;
+            }
+        }
+
+        let nearby = spatial_query.shape_intersections(
+            &Collider::circle(DANGER_RADIUS),
+            position,
+            0.0,
+            &SpatialQueryFilter::default().with_excluded_entities([entity]),
+        );
+        for (_, other_transform, temperature) in nearby
+            .into_iter()
+            .filter_map(|other| particles.get(other).ok())
+        {
+            if temperature.is_some_and(|temperature| temperature.0 >= DANGER_TEMPERATURE) {
+                let away = position - other_transform.translation.xy();
+                steering += away.normalize_or_zero() * FLEE;
+            }
+        }
+
+        let mut new_velocity = velocity.0 + steering * dt;
+        if new_velocity == Vec2::ZERO {
+            let angle = rng.random_range(0.0..std::f32::consts::TAU);
+            new_velocity = Vec2::from_angle(angle);
+        }
+        velocity.0 = new_velocity.clamp_length(BIRD_SPEED.0, BIRD_SPEED.1);
+    }
+}
@@ -0,0 +1,174 @@
+//! Short-lived elements. Particles of an element with a [`Lifetime`] age, and
+//! once their time is up they decay into another element or disappear, like
+//! fire burning out into smoke.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::SimulationOrder;
+use super::elements::{Element, Lifetime};
+use super::particle::{Particle, Tint};
+use super::rng::SimulationRng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (start_aging, age_particles)
+            .chain()
+            .in_set(SimulationOrder::Decay),
+    );
+    app.add_systems(Update, fade_particles);
+}
+
+/// How long a particle has been its current element, and how long it will
+/// last as that element, in seconds.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Age {
+    pub elapsed: f32,
+    pub lifetime: f32,
+}
+
+impl Age {
+    /// How far through its lifetime the particle is, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.elapsed / self.lifetime).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Lifetime {
+    /// Picks how long a single particle lasts.
+    pub fn roll(&self, rng: &mut impl Rng) -> f32 {
+        if self.max > self.min {
+            rng.random_range(self.min..self.max)
+        } else {
+            self.min
+        }
+    }
+}
+
+/// Starts the clock on particles that just became a short-lived element, and
+/// stops it on particles that turned into something that lasts forever.
+fn start_aging(
+    mut commands: Commands,
+    mut rng: ResMut<SimulationRng>,
+    particles: Query<(Entity, &Element), Changed<Element>>,
+) {
+    let mut particles: Vec<_> = particles.iter().collect();
+    particles.sort_by_key(|(entity, _)| *entity);
+
+    for (entity, element) in particles {
+        match element.lifetime {
+            Some(lifetime) => {
+                commands.entity(entity).try_insert(Age {
+                    elapsed: 0.0,
+                    lifetime: lifetime.roll(&mut rng.0),
+                });
+            }
+            None => {
+                commands.entity(entity).try_remove::<Age>();
+            }
+        }
+    }
+}
+
+/// Decays particles whose lifetime has run out.
+fn age_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &Element, &mut Age)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, element, mut age) in &mut particles {
+        let Some(lifetime) = element.lifetime else {
+            continue;
+        };
+        age.elapsed += dt;
+        if age.elapsed < age.lifetime {
+            continue;
+        }
+        match lifetime.decays_into {
+            Some(into) => {
+                commands
+                    .entity(entity)
+                    .try_insert(Particle::transmute(into));
+            }
+            None => {
+                commands.entity(entity).try_despawn();
+            }
+        }
+    }
+}
+
+/// Makes particles of fading elements more transparent as they age.
+fn fade_particles(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    particles: Query<(
+        &Element,
+        &Age,
+        &MeshMaterial2d<ColorMaterial>,
+        Option<&Tint>,
+    )>,
+) {
+    for (element, age, material, tint) in &particles {
+        if !element.lifetime.is_some_and(|lifetime| lifetime.fades) {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&material.0) {
+            let color = tint.map_or(element.color, |tint| tint.0);
+            material.color = color.with_alpha(1.0 - age.fraction());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn lifetime(min: f32, max: f32) -> Lifetime {
+        Lifetime {
+            min,
+            max,
+            decays_into: None,
+            fades: false,
+        }
+    }
+
+    #[test]
+    fn lifetime_roll_stays_within_its_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let lifetime = lifetime(2.0, 5.0);
+        for _ in 0..1000 {
+            let rolled = lifetime.roll(&mut rng);
+            assert!((2.0..5.0).contains(&rolled));
+        }
+    }
+
+    #[test]
+    fn lifetime_roll_without_a_range_uses_the_minimum() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(lifetime(3.0, 3.0).roll(&mut rng), 3.0);
+        assert_eq!(lifetime(3.0, 1.0).roll(&mut rng), 3.0);
+    }
+
+    #[test]
+    fn age_fraction_goes_from_zero_to_one() {
+        let age = |elapsed| Age {
+            elapsed,
+            lifetime: 4.0,
+        };
+        assert_eq!(age(0.0).fraction(), 0.0);
+        assert_eq!(age(1.0).fraction(), 0.25);
+        assert_eq!(age(4.0).fraction(), 1.0);
+        assert_eq!(age(10.0).fraction(), 1.0);
+        let instant = Age {
+            elapsed: 0.0,
+            lifetime: 0.0,
+        };
+        assert_eq!(instant.fraction(), 1.0);
+    }
+}
@@ -0,0 +1,184 @@
+//! How particles move according to their [`DiffusionRule`]. Frozen particles
+//! stay put, powders slide with friction and pile up, liquids roll and spread
+//! out sideways to level off, and gases rise and wander around. Some solids,
+//! like stone, hold still where they are put until something knocks them loose.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::SimulationOrder;
+use super::elements::{DiffusionRule, Element};
+use super::rng::SimulationRng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (dislodge_particles, spread_liquids, wander_gases)
+            .chain()
+            .in_set(SimulationOrder::Diffusion),
+    );
+}
+
+/// Friction between powder grains. Piles settle at the angle whose tangent is
+/// the friction coefficient, so this gives slopes of about 38 degrees.
+const POWDER_FRICTION: f32 = 0.8;
+
+/// Liquids slower than this are considered to be resting on something.
+const LIQUID_REST_SPEED: f32 = 0.5;
+
+/// How hard a resting liquid particle pushes sideways to level out.
+const LIQUID_SPREAD_ACCELERATION: f32 = 20.0;
+
+/// How hard gas particles jostle around. Lighter gases wander more.
+const GAS_WANDER_ACCELERATION: f32 = 15.0;
+
+/// Particles moving slower than this are considered to be at rest.
+const SETTLE_SPEED: f32 = 0.2;
+
+/// How many ticks a dislodged particle has to stay at rest before it settles
+/// back in place.
+const SETTLE_TICKS: u32 = 30;
+
+/// A particle that was knocked loose and hasn't settled back in place yet.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Dislodged {
+    resting_ticks: u32,
+}
+
+/// The physical material a particle moves with, picked by its diffusion rule.
+#[derive(Bundle, Debug, Clone)]
+pub struct Motion {
+    friction: Friction,
+    restitution: Restitution,
+    gravity_scale: GravityScale,
+    linear_damping: LinearDamping,
+    locked_axes: LockedAxes,
+    collider_density: ColliderDensity,
+}
+
+impl Motion {
+    pub fn for_element(element: &Element) -> Self {
+        let (friction, gravity_scale, linear_damping, locked_axes) = match element.diffusion_rule {
+            DiffusionRule::Frozen | DiffusionRule::Fall => (
+                POWDER_FRICTION,
+                1.0,
+                0.0,
+                // Grains that can't tumble have to slide, which keeps piles
+                // from flattening out.
+                LockedAxes::ROTATION_LOCKED,
+            ),
+            // Thick liquids drag, so they ooze instead of splashing.
+            DiffusionRule::Fill => (0.0, 1.0, 0.1 + element.viscosity, LockedAxes::new()),
+            // Gases lighter than air float up, and drag keeps them from
+            // accelerating forever.
+            DiffusionRule::Diffuse => (0.0, element.density - 1.0, 1.0, LockedAxes::new()),
+        };
+        Self {
+            friction: Friction::new(friction),
+            restitution: Restitution::new(0.0),
+            gravity_scale: GravityScale(gravity_scale),
+            linear_damping: LinearDamping(linear_damping),
+            locked_axes,
+            // Heavier elements push lighter ones around.
+            collider_density: ColliderDensity(element.density),
+        }
+    }
+}
+
+/// Knocks held particles loose when something hits them hard enough, and holds
+/// them in place again once they have been at rest for a while.
+fn dislodge_particles(
+    mut commands: Commands,
+    collisions: Collisions,
+    mut particles: Query<(
+        Entity,
+        &Element,
+        &RigidBody,
+        &LinearVelocity,
+        &CollidingEntities,
+        Option<&mut Dislodged>,
+    )>,
+) {
+    for (entity, element, body, velocity, touching, dislodged) in &mut particles {
+        let Some(dislodge_impulse) = element.dislodge_impulse else {
+            // The particle turned into something that moves freely.
+            if dislodged.is_some() {
+                commands.entity(entity).try_remove::<Dislodged>();
+            }
+            continue;
+        };
+        match dislodged {
+            None if *body == RigidBody::Static => {
+                let impulse: f32 = collisions
+                    .collisions_with(entity)
+                    .map(|contacts| contacts.total_normal_impulse_magnitude())
+                    .sum();
+                if impulse >= dislodge_impulse {
+                    commands
+                        .entity(entity)
+                        .try_insert((RigidBody::Dynamic, Dislodged::default()));
+                }
+            }
+            None => {}
+            Some(mut dislodged) => {
+                if touching.is_empty() || velocity.length() >= SETTLE_SPEED {
+                    dislodged.resting_ticks = 0;
+                    continue;
+                }
+                dislodged.resting_ticks += 1;
+                if dislodged.resting_ticks >= SETTLE_TICKS {
+                    commands
+                        .entity(entity)
+                        .try_remove::<Dislodged>()
+                        .try_insert(RigidBody::Static);
+                }
+            }
+        }
+    }
+}
+
+/// Nudges resting liquid particles to one side at random, so puddles flow
+/// outwards until they fill the bottom of their container. Thick liquids are
+/// nudged more gently.
+fn spread_liquids(
+    time: Res<Time>,
+    mut rng: ResMut<SimulationRng>,
+    mut particles: Query<(Entity, &Element, &CollidingEntities, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    let mut liquids: Vec<_> = particles
+        .iter_mut()
+        .filter(|(_, element, colliding, velocity)| {
+            element.diffusion_rule == DiffusionRule::Fill
+                && !colliding.is_empty()
+                && velocity.y.abs() < LIQUID_REST_SPEED
+        })
+        .collect();
+    liquids.sort_by_key(|(entity, ..)| *entity);
+    for (_, element, _, mut velocity) in liquids {
+        let direction = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+        let acceleration = LIQUID_SPREAD_ACCELERATION / (1.0 + element.viscosity);
+        velocity.x += direction * acceleration * dt;
+    }
+}
+
+/// Pushes gas particles in random directions. The push is stronger for lighter
+/// gases, so steam swirls while smoke drifts.
+pub(super) fn wander_gases(
+    time: Res<Time>,
+    mut rng: ResMut<SimulationRng>,
+    mut particles: Query<(Entity, &Element, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    let mut gases: Vec<_> = particles
+        .iter_mut()
+        .filter(|(_, element, _)| element.diffusion_rule == DiffusionRule::Diffuse)
+        .collect();
+    gases.sort_by_key(|(entity, ..)| *entity);
+    for (_, element, mut velocity) in gases {
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
+        let strength = GAS_WANDER_ACCELERATION / element.density.max(0.1).sqrt();
+        velocity.0 += Vec2::from_angle(angle) * strength * dt;
+    }
+}
@@ -0,0 +1,258 @@
+//! Electricity. Sparks charge the conductors they touch, and the charge hops
+//! from conductor to conductor as a pulse, heating up whatever the conductors
+//! touch along the way. That is enough to light oil or set off explosives
+//! sitting on a wire.
+//!
+//! Thunder calls down a branching bolt of sparks from the top of the sandbox.
+
+use avian2d::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+use rand::Rng;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType};
+use super::heat::Temperature;
+use super::particle::{Particle, ParticleBudget, neighbours};
+use super::rng::SimulationRng;
+use super::sandbox::{ScreenWrap, half_extents};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            strike_thunder,
+            charge_from_sparks,
+            conduct_charge,
+            recover_conductors,
+        )
+            .chain()
+            .in_set(SimulationOrder::Electricity),
+    );
+    app.add_systems(Update, show_charge);
+}
+
+/// How many ticks a conductor rests after carrying a pulse. While resting it
+/// can't be charged again, so pulses travel away from where they started
+/// instead of bouncing back and forth.
+const RECOVERY_TICKS: u32 = 8;
+
+/// Heat a charged conductor gives each non-conducting particle touching it.
+const CHARGE_HEAT: f32 = 40.0;
+
+/// Distance between the sparks that make up a bolt of lightning.
+const BOLT_STEP: f32 = 1.0;
+
+/// How far a bolt can jerk sideways with each step.
+const BOLT_JITTER: f32 = 1.5;
+
+/// How far from its target a bolt can start, sideways.
+const BOLT_SPREAD: f32 = 20.0;
+
+/// Chance per step of a side branch splitting off a bolt.
+const BRANCH_CHANCE: f64 = 0.04;
+
+/// Seconds after a bolt before thunder can strike again.
+const THUNDER_COOLDOWN: f32 = 0.5;
+
+/// A conductor carrying an electric pulse this tick.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Charged;
+
+/// A conductor that just carried a pulse and can't be charged again yet.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Discharged {
+    ticks_left: u32,
+}
+
+/// Replaces newly placed thunder with a bolt of lightning striking down onto
+/// it. A bolt is cut short once the [`ParticleBudget`] is used up.
+fn strike_thunder(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cooldown: Local<f32>,
+    mut rng: ResMut<SimulationRng>,
+    budget: Res<ParticleBudget>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    thunder: Query<(Entity, &ElementType, &Transform), Added<ElementType>>,
+    particles: Query<(), With<ElementType>>,
+) {
+    *cooldown = (*cooldown - time.delta_secs()).max(0.0);
+    let mut strikes: Vec<(Entity, Vec2)> = thunder
+        .iter()
+        .filter(|(_, element_type, _)| **element_type == ElementType::Thunder)
+        .map(|(entity, _, transform)| (entity, transform.translation.xy()))
+        .collect();
+    strikes.sort_by_key(|(entity, _)| *entity);
+
+    let top = half_extents(&window).y;
+    let mut count = particles.iter().count();
+    for (entity, target) in strikes {
+        commands
+            .entity(entity)
+            .try_insert(Particle::transmute(ElementType::Spark));
+        if *cooldown > 0.0 {
+            continue;
+        }
+        *cooldown = THUNDER_COOLDOWN;
+        let bolt = bolt_path(&mut rng.0, top, target, budget.0.saturating_sub(count));
+        count += bolt.len();
+        for position in bolt {
+            commands.spawn((Particle::new(ElementType::Spark, position), ScreenWrap));
+        }
+    }
+}
+
+/// Points along a jagged bolt from the top of the sandbox down to `target`,
+/// including the branches that split off it. The bolt stops short after
+/// `max_points` points.
+fn bolt_path(rng: &mut impl Rng, top: f32, target: Vec2, max_points: usize) -> Vec<Vec2> {
+    let mut points = Vec::new();
+    let start = target.x + rng.random_range(-BOLT_SPREAD..BOLT_SPREAD);
+    let mut position = Vec2::new(start, top);
+    while position.y > target.y && points.len() < max_points {
+        // Drift towards the target so the bolt lands where it was placed.
+        let steps_left = ((position.y - target.y) / BOLT_STEP).max(1.0);
+        let drift = (target.x - position.x) / steps_left;
+        position += Vec2::new(
+            drift + rng.random_range(-BOLT_JITTER..BOLT_JITTER),
+            -BOLT_STEP,
+        );
+        points.push(position);
+
+        if rng.random_bool(BRANCH_CHANCE) {
+            let side = if rng.random_bool(0.5) { 1.0 } else { -1.0 };
+            let mut branch = position;
+            for _ in 0..rng.random_range(5..20) {
+                branch += Vec2::new(
+                    side * BOLT_STEP + rng.random_range(-BOLT_JITTER..BOLT_JITTER),
+                    -BOLT_STEP,
+                );
+                points.push(branch);
+            }
+        }
+    }
+    points.truncate(max_points);
+    points
+}
+
+/// Charges conductors touched by a spark.
+fn charge_from_sparks(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    sparks: Query<(Entity, &ElementType, &Transform)>,
+    conductors: Query<&Element, (Without<Charged>, Without<Discharged>)>,
+) {
+    for (entity, element_type, transform) in &sparks {
+        if *element_type != ElementType::Spark {
+            continue;
+        }
+        for other in neighbours(&spatial_query, entity, transform.translation.xy()) {
+            if conductors
+                .get(other)
+                .is_ok_and(|element| element.electrical_conductivity > 0.0)
+            {
+                commands.entity(other).try_insert(Charged);
+            }
+        }
+    }
+}
+
+/// Passes each pulse on to the conductors touching it, and heats up anything
+/// else touching it.
+fn conduct_charge(
+    mut commands: Commands,
+    mut rng: ResMut<SimulationRng>,
+    spatial_query: SpatialQuery,
+    charged: Query<(Entity, &Transform), With<Charged>>,
+    mut particles: Query<
+        (&Element, &mut Temperature, Has<Charged>, Has<Discharged>),
+        With<ElementType>,
+    >,
+) {
+    let mut pulses: Vec<(Entity, Vec<Entity>)> = charged
+        .iter()
+        .map(|(entity, transform)| {
+            let touching = neighbours(&spatial_query, entity, transform.translation.xy());
+            (entity, touching)
+        })
+        .collect();
+    pulses.sort_by_key(|(entity, _)| *entity);
+
+    for (entity, touching) in pulses {
+        for other in touching {
+            let Ok((element, mut temperature, charged, discharged)) = particles.get_mut(other)
+            else {
+                continue;
+            };
+            if element.electrical_conductivity <= 0.0 {
+                temperature.0 += CHARGE_HEAT / element.thermal_mass().max(f32::EPSILON);
+            } else if !charged
+                && !discharged
+                && rng.random_bool(element.electrical_conductivity.min(1.0) as f64)
+            {
+                commands.entity(other).try_insert(Charged);
+            }
+        }
+        commands
+            .entity(entity)
+            .try_remove::<Charged>()
+            .try_insert(Discharged {
+                ticks_left: RECOVERY_TICKS,
+            });
+    }
+}
+
+/// Lets conductors that carried a pulse be charged again after a while.
+fn recover_conductors(mut commands: Commands, mut resting: Query<(Entity, &mut Discharged)>) {
+    for (entity, mut discharged) in &mut resting {
+        discharged.ticks_left = discharged.ticks_left.saturating_sub(1);
+        if discharged.ticks_left == 0 {
+            commands.entity(entity).try_remove::<Discharged>();
+        }
+    }
+}
+
+/// Lights up conductors while they carry a pulse.
+fn show_charge(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    conductors: Query<
+        (&Element, Has<Charged>, &MeshMaterial2d<ColorMaterial>),
+        Or<(Added<Charged>, Added<Discharged>)>,
+    >,
+) {
+    let spark_color = Element::from_type(ElementType::Spark).color;
+    for (element, charged, material) in &conductors {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.color = if charged { spark_color } else { element.color };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn bolt_path_reaches_its_target() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let target = Vec2::new(10.0, -50.0);
+        let bolt = bolt_path(&mut rng, 100.0, target, usize::MAX);
+        assert!(bolt.len() >= 150);
+        assert!(
+            bolt.iter()
+                .any(|point| point.distance(target) < BOLT_STEP * 2.0)
+        );
+    }
+
+    #[test]
+    fn bolt_path_stops_at_the_particle_budget() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let bolt = bolt_path(&mut rng, 100.0, Vec2::new(0.0, -50.0), 40);
+            assert_eq!(bolt.len(), 40);
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(bolt_path(&mut rng, 100.0, Vec2::new(0.0, -50.0), 0).is_empty());
+    }
+}
@@ -1,11 +1,21 @@
 use std::hash::Hash;
 
 use bevy::{
-    color::palettes::css::{BISQUE, GREY, MAROON, ORANGE_RED, ROYAL_BLUE, TAN, WHITE},
+    color::palettes::css::{
+        ALICE_BLUE, BISQUE, BROWN, CRIMSON, DARK_GRAY, DARK_ORANGE, DARK_RED, DARK_SLATE_GRAY,
+        DARK_VIOLET, DEEP_PINK, DIM_GREY, FOREST_GREEN, GAINSBORO, GHOST_WHITE, GOLD, GREEN,
+        GREEN_YELLOW, GREY, LAVENDER, LIGHT_BLUE, LIGHT_GRAY, LIGHT_SLATE_GRAY, LIME_GREEN, MAROON,
+        OLIVE, ORANGE, ORANGE_RED, PALE_TURQUOISE, PINK, RED, ROYAL_BLUE, SADDLE_BROWN, SILVER,
+        SKY_BLUE, SLATE_GREY, STEEL_BLUE, TAN, WHITE, YELLOW,
+    },
     prelude::*,
 };
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+use super::heat::AMBIENT_TEMPERATURE;
+
+/// What a particle is made of. Reactions are looked up by the element types of
+/// the particles involved.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ElementType {
     #[default]
     Powder,
@@ -14,7 +24,90 @@ pub enum ElementType {
     Oil,
     Fire,
     Steam,
+    Smoke,
     Wall,
+    Ice,
+    Magma,
+    Stone,
+    Glass,
+    C4,
+    Bomb,
+    Nitro,
+    Spark,
+    Metal,
+    Mercury,
+    Thunder,
+    SaltWater,
+    Fuse,
+    Ash,
+    Acid,
+    Gas,
+    Virus,
+    Clone,
+    Seed,
+    Vine,
+    Ant,
+    Bird,
+    Fan,
+    Wind,
+    Air,
+    GPower,
+    Pump,
+    Cloud,
+    Laser,
+    Salt,
+    Soapy,
+    Bubble,
+    Torch,
+    Firework,
+}
+
+impl ElementType {
+    /// Every element type, for building rules that apply to many elements.
+    pub const ALL: &[ElementType] = &[
+        ElementType::Powder,
+        ElementType::Sand,
+        ElementType::Water,
+        ElementType::Oil,
+        ElementType::Fire,
+        ElementType::Steam,
+        ElementType::Smoke,
+        ElementType::Wall,
+        ElementType::Ice,
+        ElementType::Magma,
+        ElementType::Stone,
+        ElementType::Glass,
+        ElementType::C4,
+        ElementType::Bomb,
+        ElementType::Nitro,
+        ElementType::Spark,
+        ElementType::Metal,
+        ElementType::Mercury,
+        ElementType::Thunder,
+        ElementType::SaltWater,
+        ElementType::Fuse,
+        ElementType::Ash,
+        ElementType::Acid,
+        ElementType::Gas,
+        ElementType::Virus,
+        ElementType::Clone,
+        ElementType::Seed,
+        ElementType::Vine,
+        ElementType::Ant,
+        ElementType::Bird,
+        ElementType::Fan,
+        ElementType::Wind,
+        ElementType::Air,
+        ElementType::GPower,
+        ElementType::Pump,
+        ElementType::Cloud,
+        ElementType::Laser,
+        ElementType::Salt,
+        ElementType::Soapy,
+        ElementType::Bubble,
+        ElementType::Torch,
+        ElementType::Firework,
+    ];
 }
 
 /// Indicates whether the particle is frozen in place or free to move around.
@@ -33,7 +126,110 @@ pub enum DiffusionRule {
 pub struct Element {
     pub color: Color,
     pub diffusion_rule: DiffusionRule,
-    pub density: f32
+    pub density: f32,
+    /// The temperature particles of this element start out at, in degrees Celsius.
+    pub temperature: f32,
+    /// Heat needed to warm one unit of mass by one degree.
+    pub heat_capacity: f32,
+    /// How quickly heat flows into or out of this element when it touches
+    /// something, per degree of difference.
+    pub thermal_conductivity: f32,
+    /// What this element turns into when it gets too cold, like water freezing.
+    pub cools_into: Option<PhaseChange>,
+    /// What this element turns into when it gets too hot, like water boiling.
+    pub heats_into: Option<PhaseChange>,
+    /// How long particles of this element last, for things like fire that burn
+    /// out. `None` means they last forever.
+    pub lifetime: Option<Lifetime>,
+    /// How this element blows up, if it is an explosive.
+    pub explosive: Option<Explosive>,
+    /// How strong a blast has to be to break a particle of this element.
+    pub blast_resistance: f32,
+    /// What a particle of this element breaks into when a blast is too strong
+    /// for it, like glass shattering into sand. `None` means it is destroyed.
+    pub shatters_into: Option<ElementType>,
+    /// How readily this element passes on an electric charge, from 0 for an
+    /// insulator to 1 for a perfect conductor.
+    pub electrical_conductivity: f32,
+    /// How well this element holds up against acid, from 0 for dissolving on
+    /// contact to 1 for not dissolving at all.
+    pub corrosion_resistance: f32,
+    /// How thick this element is as a liquid, from 0 for something runny like
+    /// water. Thicker liquids flow slower and take longer to level out.
+    pub viscosity: f32,
+    /// How hard a particle of this element has to be hit to knock it loose.
+    /// Until then it stays where it was put, and once it comes to rest again
+    /// it settles back in place. `None` means it always moves freely.
+    pub dislodge_impulse: Option<f32>,
+}
+
+/// A change of state at a given temperature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseChange {
+    /// The temperature at which the change happens, in degrees Celsius.
+    pub temperature: f32,
+    /// The element the particle becomes.
+    pub into: ElementType,
+}
+
+impl PhaseChange {
+    pub const fn new(temperature: f32, into: ElementType) -> Option<Self> {
+        Some(Self { temperature, into })
+    }
+}
+
+/// How long a short-lived element lasts and what is left behind afterwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lifetime {
+    /// The shortest a particle lasts, in seconds.
+    pub min: f32,
+    /// The longest a particle lasts, in seconds. Each particle picks a lifetime
+    /// at random between `min` and `max`.
+    pub max: f32,
+    /// The element the particle becomes when its time is up, or `None` if it
+    /// disappears.
+    pub decays_into: Option<ElementType>,
+    /// Whether the particle fades out as it ages.
+    pub fades: bool,
+}
+
+/// How an explosive element is set off and how big a blast it makes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Explosive {
+    /// How far the blast reaches, in world units.
+    pub radius: f32,
+    /// The impulse the blast gives a particle right next to it. Particles
+    /// further away get less, down to nothing at the edge of the blast.
+    pub power: f32,
+    /// The explosive goes off when it, or anything touching it, gets this hot.
+    pub ignition_temperature: f32,
+    /// The explosive goes off when its speed changes by more than this in a
+    /// single tick, like when it is dropped onto something. `None` means it
+    /// doesn't react to impacts.
+    pub impact_speed: Option<f32>,
+}
+
+impl Default for Element {
+    fn default() -> Self {
+        Self {
+            color: WHITE.into(),
+            diffusion_rule: DiffusionRule::Fall,
+            density: 1.0,
+            temperature: AMBIENT_TEMPERATURE,
+            heat_capacity: 1.0,
+            thermal_conductivity: 0.1,
+            cools_into: None,
+            heats_into: None,
+            lifetime: None,
+            explosive: None,
+            blast_resistance: 10.0,
+            shatters_into: None,
+            electrical_conductivity: 0.0,
+            corrosion_resistance: 1.0,
+            viscosity: 0.0,
+            dislodge_impulse: None,
+        }
+    }
 }
 
 impl Element {
@@ -43,36 +239,498 @@ impl Element {
                 color: BISQUE.into(),
                 diffusion_rule: DiffusionRule::Fall,
                 density: 1.0,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.1,
+                corrosion_resistance: 0.0,
+                ..default()
             },
             ElementType::Sand => Self {
                 color: TAN.into(),
                 diffusion_rule: DiffusionRule::Fall,
                 density: 1.5,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.3,
+                heats_into: PhaseChange::new(1000.0, ElementType::Glass),
+                corrosion_resistance: 0.4,
+                ..default()
             },
             ElementType::Water => Self {
                 color: ROYAL_BLUE.into(),
                 diffusion_rule: DiffusionRule::Fill,
                 density: 1.0,
+                heat_capacity: 4.2,
+                thermal_conductivity: 0.6,
+                cools_into: PhaseChange::new(0.0, ElementType::Ice),
+                heats_into: PhaseChange::new(100.0, ElementType::Steam),
+                electrical_conductivity: 0.3,
+                ..default()
             },
             ElementType::Oil => Self {
                 color: MAROON.into(),
                 diffusion_rule: DiffusionRule::Fill,
                 density: 0.8,
+                heat_capacity: 2.0,
+                thermal_conductivity: 0.15,
+                corrosion_resistance: 0.7,
+                ..default()
             },
             ElementType::Fire => Self {
                 color: ORANGE_RED.into(),
                 diffusion_rule: DiffusionRule::Diffuse,
                 density: 0.5,
+                temperature: 800.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.5,
+                lifetime: Some(Lifetime {
+                    min: 0.5,
+                    max: 1.5,
+                    decays_into: Some(ElementType::Smoke),
+                    fades: false,
+                }),
+                ..default()
             },
             ElementType::Steam => Self {
                 color: WHITE.into(),
                 diffusion_rule: DiffusionRule::Diffuse,
                 density: 0.1,
+                temperature: 110.0,
+                heat_capacity: 2.0,
+                thermal_conductivity: 0.1,
+                cools_into: PhaseChange::new(95.0, ElementType::Water),
+                // Steam that drifts around long enough condenses, even if it
+                // never touches anything cold.
+                lifetime: Some(Lifetime {
+                    min: 8.0,
+                    max: 15.0,
+                    decays_into: Some(ElementType::Water),
+                    fades: false,
+                }),
+                ..default()
+            },
+            ElementType::Smoke => Self {
+                color: DIM_GREY.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 0.2,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.05,
+                lifetime: Some(Lifetime {
+                    min: 2.0,
+                    max: 5.0,
+                    decays_into: None,
+                    fades: true,
+                }),
+                ..default()
             },
             ElementType::Wall => Self {
                 color: GREY.into(),
                 diffusion_rule: DiffusionRule::Frozen,
                 density: 2.0,
+                heat_capacity: 0.9,
+                thermal_conductivity: 0.5,
+                blast_resistance: f32::INFINITY,
+                ..default()
+            },
+            ElementType::Ice => Self {
+                color: PALE_TURQUOISE.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 0.9,
+                temperature: -10.0,
+                heat_capacity: 2.1,
+                thermal_conductivity: 0.8,
+                // Melt slightly above freezing so a particle sitting right at
+                // zero doesn't flicker between ice and water.
+                heats_into: PhaseChange::new(2.0, ElementType::Water),
+                blast_resistance: 15.0,
+                shatters_into: Some(ElementType::Water),
+                corrosion_resistance: 0.5,
+                ..default()
+            },
+            // Magma cools into stone, stone is broken down into sand, sand
+            // melts into glass and glass melts back into magma.
+            ElementType::Magma => Self {
+                color: DARK_ORANGE.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 2.5,
+                temperature: 1200.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.4,
+                cools_into: PhaseChange::new(700.0, ElementType::Stone),
+                viscosity: 4.0,
+                ..default()
+            },
+            ElementType::Stone => Self {
+                color: SLATE_GREY.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 2.6,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.4,
+                heats_into: PhaseChange::new(1200.0, ElementType::Magma),
+                blast_resistance: 40.0,
+                shatters_into: Some(ElementType::Sand),
+                corrosion_resistance: 0.8,
+                dislodge_impulse: Some(5.0),
+                ..default()
+            },
+            ElementType::Glass => Self {
+                color: LIGHT_BLUE.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.5,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.2,
+                heats_into: PhaseChange::new(1400.0, ElementType::Magma),
+                blast_resistance: 5.0,
+                shatters_into: Some(ElementType::Sand),
+                ..default()
+            },
+            // Plastic explosive. It sticks where it is put and is hard to set
+            // off, but makes the biggest blast.
+            ElementType::C4 => Self {
+                color: CRIMSON.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 1.6,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.2,
+                explosive: Some(Explosive {
+                    radius: 16.0,
+                    power: 120.0,
+                    ignition_temperature: 400.0,
+                    impact_speed: None,
+                }),
+                corrosion_resistance: 0.6,
+                ..default()
+            },
+            ElementType::Bomb => Self {
+                color: DARK_RED.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 3.0,
+                heat_capacity: 0.5,
+                thermal_conductivity: 0.5,
+                explosive: Some(Explosive {
+                    radius: 10.0,
+                    power: 80.0,
+                    ignition_temperature: 300.0,
+                    impact_speed: Some(20.0),
+                }),
+                corrosion_resistance: 0.9,
+                ..default()
+            },
+            // Nitroglycerin. A liquid that goes off at the slightest knock.
+            ElementType::Nitro => Self {
+                color: ORANGE.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 1.6,
+                heat_capacity: 1.2,
+                thermal_conductivity: 0.2,
+                explosive: Some(Explosive {
+                    radius: 8.0,
+                    power: 60.0,
+                    ignition_temperature: 200.0,
+                    impact_speed: Some(6.0),
+                }),
+                ..default()
+            },
+            ElementType::Spark => Self {
+                color: YELLOW.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                temperature: 1500.0,
+                heat_capacity: 0.1,
+                thermal_conductivity: 1.0,
+                lifetime: Some(Lifetime {
+                    min: 0.1,
+                    max: 0.3,
+                    decays_into: None,
+                    fades: true,
+                }),
+                ..default()
+            },
+            ElementType::Metal => Self {
+                color: SILVER.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 7.8,
+                heat_capacity: 0.5,
+                thermal_conductivity: 1.0,
+                heats_into: PhaseChange::new(1500.0, ElementType::Magma),
+                blast_resistance: 80.0,
+                electrical_conductivity: 1.0,
+                corrosion_resistance: 0.95,
+                ..default()
+            },
+            ElementType::Mercury => Self {
+                color: LIGHT_GRAY.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 13.5,
+                heat_capacity: 0.14,
+                thermal_conductivity: 0.8,
+                electrical_conductivity: 0.9,
+                ..default()
+            },
+            // Placing thunder calls down a bolt of lightning onto that spot. If
+            // the bolt doesn't come, it fizzles out into a spark.
+            ElementType::Thunder => Self {
+                color: YELLOW.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                temperature: 1500.0,
+                heat_capacity: 0.1,
+                thermal_conductivity: 1.0,
+                lifetime: Some(Lifetime {
+                    min: 0.0,
+                    max: 0.0,
+                    decays_into: Some(ElementType::Spark),
+                    fades: false,
+                }),
+                ..default()
+            },
+            // Salt dissolved in water, see `ReactionRegistry::register_solution`.
+            // The dissolved salt makes the water a much better conductor.
+            // Boiling or freezing it leaves the salt behind.
+            ElementType::SaltWater => Self {
+                color: STEEL_BLUE.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 1.03,
+                heat_capacity: 3.9,
+                thermal_conductivity: 0.6,
+                electrical_conductivity: 0.8,
+                ..default()
+            },
+            // Fuses burn at a steady pace once lit instead of reacting, see
+            // the `fuse` module.
+            ElementType::Fuse => Self {
+                color: SADDLE_BROWN.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 0.8,
+                heat_capacity: 1.5,
+                thermal_conductivity: 0.05,
+                corrosion_resistance: 0.2,
+                ..default()
+            },
+            ElementType::Ash => Self {
+                color: DARK_GRAY.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 0.6,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.05,
+                corrosion_resistance: 0.0,
+                ..default()
+            },
+            ElementType::Acid => Self {
+                color: GREEN_YELLOW.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 1.1,
+                heat_capacity: 3.0,
+                thermal_conductivity: 0.5,
+                electrical_conductivity: 0.6,
+                ..default()
+            },
+            // Flammable gas, given off by acid as it eats through things.
+            ElementType::Gas => Self {
+                color: SILVER.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 0.6,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.05,
+                ..default()
+            },
+            // Spreads to whatever it touches, see the virus reactions in the
+            // `reaction` module. Heat kills it.
+            ElementType::Virus => Self {
+                color: GREEN.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 1.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.1,
+                heats_into: PhaseChange::new(80.0, ElementType::Smoke),
+                corrosion_resistance: 0.0,
+                ..default()
+            },
+            // Copies the first element it touches, see the `cloner` module.
+            ElementType::Clone => Self {
+                color: LIME_GREEN.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.1,
+                blast_resistance: f32::INFINITY,
+                ..default()
+            },
+            // Seeds and vines grow into plants, see the `plant` module.
+            ElementType::Seed => Self {
+                color: OLIVE.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 1.1,
+                heat_capacity: 1.5,
+                thermal_conductivity: 0.1,
+                corrosion_resistance: 0.0,
+                ..default()
+            },
+            ElementType::Vine => Self {
+                color: FOREST_GREEN.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 0.9,
+                heat_capacity: 2.0,
+                thermal_conductivity: 0.1,
+                corrosion_resistance: 0.2,
+                ..default()
+            },
+            // Ants and birds are creatures with minds of their own, see the
+            // `creature` module.
+            ElementType::Ant => Self {
+                color: BROWN.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 0.8,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.1,
+                heats_into: PhaseChange::new(150.0, ElementType::Ash),
+                blast_resistance: 2.0,
+                corrosion_resistance: 0.0,
+                ..default()
+            },
+            // Birds drift like a gas that weighs as much as the air, so they
+            // hang wherever they fly to.
+            ElementType::Bird => Self {
+                color: GOLD.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.1,
+                heats_into: PhaseChange::new(150.0, ElementType::Ash),
+                blast_resistance: 2.0,
+                corrosion_resistance: 0.0,
+                ..default()
+            },
+            // Fans, wind, air and G-power push and pull other particles
+            // around, see the `force` module.
+            ElementType::Fan => Self {
+                color: LIGHT_SLATE_GRAY.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.0,
+                heat_capacity: 0.5,
+                thermal_conductivity: 0.5,
+                electrical_conductivity: 0.5,
+                ..default()
+            },
+            ElementType::Wind => Self {
+                color: SKY_BLUE.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                lifetime: Some(Lifetime {
+                    min: 0.5,
+                    max: 1.0,
+                    decays_into: None,
+                    fades: true,
+                }),
+                ..default()
+            },
+            ElementType::Air => Self {
+                color: LAVENDER.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                lifetime: Some(Lifetime {
+                    min: 0.2,
+                    max: 0.4,
+                    decays_into: None,
+                    fades: true,
+                }),
+                ..default()
+            },
+            ElementType::GPower => Self {
+                color: DARK_VIOLET.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 2.0,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.2,
+                ..default()
+            },
+            // Moves liquids through pipes, see the `pump` module.
+            ElementType::Pump => Self {
+                color: DARK_SLATE_GRAY.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.0,
+                heat_capacity: 0.5,
+                thermal_conductivity: 0.5,
+                blast_resistance: 40.0,
+                ..default()
+            },
+            // Collects steam and rains it back down, see the `cloud` module.
+            // As heavy as the air, so it hangs where it is put.
+            ElementType::Cloud => Self {
+                color: GAINSBORO.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 1.0,
+                heat_capacity: 2.0,
+                thermal_conductivity: 0.05,
+                ..default()
+            },
+            // Fires a beam of light, see the `laser` module.
+            ElementType::Laser => Self {
+                color: RED.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.0,
+                heat_capacity: 0.5,
+                thermal_conductivity: 0.5,
+                blast_resistance: 40.0,
+                ..default()
+            },
+            // Dissolves in water, see `ReactionRegistry::register_solution`.
+            ElementType::Salt => Self {
+                color: GHOST_WHITE.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 2.2,
+                heat_capacity: 0.9,
+                thermal_conductivity: 0.1,
+                corrosion_resistance: 0.6,
+                ..default()
+            },
+            // Soap foams up water it touches into bubbles. It stays runny in the
+            // cold rather than freezing into plain ice and losing the soap.
+            ElementType::Soapy => Self {
+                color: PINK.into(),
+                diffusion_rule: DiffusionRule::Fill,
+                density: 1.05,
+                heat_capacity: 3.5,
+                thermal_conductivity: 0.5,
+                corrosion_resistance: 0.5,
+                ..default()
+            },
+            // Lighter than air, so bubbles float up until they pop. They can
+            // trap a bit of gas on the way, see the `bubble` module.
+            ElementType::Bubble => Self {
+                color: ALICE_BLUE.into(),
+                diffusion_rule: DiffusionRule::Diffuse,
+                density: 0.7,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.05,
+                lifetime: Some(Lifetime {
+                    min: 3.0,
+                    max: 8.0,
+                    decays_into: None,
+                    fades: true,
+                }),
+                blast_resistance: 0.5,
+                ..default()
+            },
+            // A flame that never goes out, see the `torch` module.
+            ElementType::Torch => Self {
+                color: ORANGE.into(),
+                diffusion_rule: DiffusionRule::Frozen,
+                density: 2.0,
+                temperature: 900.0,
+                heat_capacity: 1.0,
+                thermal_conductivity: 0.5,
+                blast_resistance: 40.0,
+                ..default()
+            },
+            // Shoots up when lit and bursts into sparks, see the `firework`
+            // module.
+            ElementType::Firework => Self {
+                color: DEEP_PINK.into(),
+                diffusion_rule: DiffusionRule::Fall,
+                density: 1.2,
+                heat_capacity: 0.8,
+                thermal_conductivity: 0.2,
+                blast_resistance: 2.0,
+                ..default()
             },
         }
     }
@@ -0,0 +1,173 @@
+//! Explosives. Particles of an element with an [`Explosive`] go off when they
+//! get hot enough, touch something hot enough, or slam into something hard
+//! enough. The blast throws everything in range outwards, breaks anything too
+//! weak to withstand it and sets off other explosives, so one detonation can
+//! ripple through a whole pile.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashMap as Map;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType, Explosive};
+use super::heat::Temperature;
+use super::particle::{Particle, neighbours};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (ignite_explosives, detonate)
+            .chain()
+            .in_set(SimulationOrder::Explosion),
+    );
+}
+
+/// Marks an explosive that goes off this tick.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Detonating;
+
+/// An impact-sensitive explosive's velocity last tick, to tell when it hits
+/// something.
+#[derive(Component, Debug, Default, Clone, Copy, Deref, DerefMut)]
+struct LastVelocity(Vec2);
+
+/// Marks explosives that are hot enough, touching something hot enough or
+/// have just hit something hard enough to go off.
+fn ignite_explosives(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut explosives: Query<
+        (
+            Entity,
+            &Element,
+            &Transform,
+            &Temperature,
+            Option<&LinearVelocity>,
+            Option<&mut LastVelocity>,
+        ),
+        Without<Detonating>,
+    >,
+    temperatures: Query<&Temperature>,
+) {
+    for (entity, element, transform, temperature, velocity, last_velocity) in &mut explosives {
+        let Some(explosive) = element.explosive else {
+            continue;
+        };
+
+        let velocity = velocity.map_or(Vec2::ZERO, |velocity| velocity.0);
+        let impact = match (explosive.impact_speed, last_velocity) {
+            (Some(impact_speed), Some(mut last_velocity)) => {
+                let hit = (last_velocity.0 - velocity).length() > impact_speed;
+                last_velocity.0 = velocity;
+                hit
+            }
+            (Some(_), None) => {
+                commands.entity(entity).try_insert(LastVelocity(velocity));
+                false
+            }
+            (None, _) => false,
+        };
+
+        let heated = temperature.0 >= explosive.ignition_temperature
+            || neighbours(&spatial_query, entity, transform.translation.xy())
+                .into_iter()
+                .filter_map(|other| temperatures.get(other).ok())
+                .any(|other| other.0 >= explosive.ignition_temperature);
+
+        if impact || heated {
+            commands.entity(entity).try_insert(Detonating);
+        }
+    }
+}
+
+/// Blows up every [`Detonating`] explosive. Each one turns into fire and
+/// pushes away, breaks or sets off everything within its blast radius.
+fn detonate(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    particles: Query<(Entity, &Element, &Transform, Has<Detonating>)>,
+) {
+    let mut blasts: Vec<(Entity, Vec2, Explosive)> = particles
+        .iter()
+        .filter(|(.., detonating)| *detonating)
+        .filter_map(|(entity, element, transform, _)| {
+            let explosive = element.explosive?;
+            Some((entity, transform.translation.xy(), explosive))
+        })
+        .collect();
+    if blasts.is_empty() {
+        return;
+    }
+    blasts.sort_by_key(|(entity, ..)| *entity);
+
+    let fire_temperature = Element::from_type(ElementType::Fire).temperature;
+    let mut impulses: Map<Entity, Vec2> = Map::new();
+    let mut strongest_blast: Map<Entity, f32> = Map::new();
+    for (entity, center, explosive) in &blasts {
+        commands
+            .entity(*entity)
+            .try_remove::<Detonating>()
+            .try_insert((
+                Particle::transmute(ElementType::Fire),
+                Temperature(fire_temperature),
+            ));
+
+        let mut caught = spatial_query.shape_intersections(
+            &Collider::circle(explosive.radius),
+            *center,
+            0.0,
+            &SpatialQueryFilter::default(),
+        );
+        caught.sort();
+        for other in caught {
+            if other == *entity {
+                continue;
+            }
+            let Ok((_, _, transform, _)) = particles.get(other) else {
+                continue;
+            };
+            // The blast is strongest at its center and fades out towards the
+            // edge.
+            let offset = transform.translation.xy() - *center;
+            let falloff = (1.0 - offset.length() / explosive.radius).max(0.0);
+            let strength = explosive.power * falloff;
+            *impulses.entry(other).or_default() += offset.normalize_or_zero() * strength;
+            let strongest = strongest_blast.entry(other).or_default();
+            *strongest = strongest.max(strength);
+        }
+    }
+
+    let mut impulses: Vec<(Entity, Vec2)> = impulses.into_iter().collect();
+    impulses.sort_by_key(|(entity, _)| *entity);
+    for (entity, impulse) in impulses {
+        commands
+            .entity(entity)
+            .try_insert(ExternalImpulse::new(impulse));
+    }
+    let mut strongest_blast: Vec<(Entity, f32)> = strongest_blast.into_iter().collect();
+    strongest_blast.sort_by_key(|(entity, _)| *entity);
+    for (entity, strength) in strongest_blast {
+        let Ok((_, element, _, detonating)) = particles.get(entity) else {
+            continue;
+        };
+        if detonating {
+            continue;
+        }
+        if element.explosive.is_some() {
+            // Set off explosives caught in the blast. They go off next tick,
+            // so a chain of detonations travels outwards.
+            commands.entity(entity).try_insert(Detonating);
+        } else if strength > element.blast_resistance {
+            match element.shatters_into {
+                Some(into) => {
+                    commands
+                        .entity(entity)
+                        .try_insert(Particle::transmute(into));
+                }
+                None => {
+                    commands.entity(entity).try_despawn();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Fireworks. A firework that gets hot enough, or touches something that is,
+//! shoots straight up. Once it stops climbing it bursts into a ring of sparks
+//! of a single random color, hot enough to light the next firework along.
+
+use avian2d::prelude::*;
+use bevy::{
+    color::palettes::css::{DEEP_SKY_BLUE, GOLD, LIME, MAGENTA, RED, WHITE},
+    prelude::*,
+};
+use rand::Rng;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::heat::Temperature;
+use super::particle::{Particle, ParticleBudget, Tint, neighbours};
+use super::rng::SimulationRng;
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (light_fireworks, burst_fireworks)
+            .chain()
+            .in_set(SimulationOrder::Firework),
+    );
+}
+
+/// Fireworks go off when they, or anything touching them, get this hot.
+const IGNITION_TEMPERATURE: f32 = 200.0;
+
+/// How fast a lit firework shoots up.
+const LAUNCH_SPEED: f32 = 40.0;
+
+/// How many sparks a firework bursts into, if the [`ParticleBudget`] allows.
+const BURST_SPARKS: usize = 24;
+
+/// Slowest and fastest the sparks of a burst fly out.
+const BURST_SPEED: (f32, f32) = (20.0, 40.0);
+
+/// The colors a burst can be.
+const BURST_COLORS: [Srgba; 6] = [RED, GOLD, LIME, DEEP_SKY_BLUE, MAGENTA, WHITE];
+
+/// A firework that has been lit and is on its way up.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct Launched;
+
+/// Launches fireworks that are hot enough or touch something that is.
+fn light_fireworks(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    mut fireworks: Query<
+        (
+            Entity,
+            &ElementType,
+            &Transform,
+            &Temperature,
+            &mut LinearVelocity,
+        ),
+        Without<Launched>,
+    >,
+    temperatures: Query<&Temperature>,
+) {
+    for (entity, element_type, transform, temperature, mut velocity) in &mut fireworks {
+        if *element_type != ElementType::Firework {
+            continue;
+        }
+        let lit = temperature.0 >= IGNITION_TEMPERATURE
+            || neighbours(&spatial_query, entity, transform.translation.xy())
+                .into_iter()
+                .filter_map(|other| temperatures.get(other).ok())
+                .any(|other| other.0 >= IGNITION_TEMPERATURE);
+        if lit {
+            velocity.0 = Vec2::Y * LAUNCH_SPEED;
+            commands.entity(entity).try_insert(Launched);
+        }
+    }
+}
+
+/// Bursts launched fireworks into sparks once they stop climbing, whether
+/// they reached the top of their flight or hit something on the way.
+fn burst_fireworks(
+    mut commands: Commands,
+    mut rng: ResMut<SimulationRng>,
+    budget: Res<ParticleBudget>,
+    fireworks: Query<(Entity, &ElementType, &Transform, &LinearVelocity), With<Launched>>,
+    particles: Query<(), With<ElementType>>,
+) {
+    let mut bursting: Vec<(Entity, Vec2)> = Vec::new();
+    for (entity, element_type, transform, velocity) in &fireworks {
+        if *element_type != ElementType::Firework {
+            // Burned up or dissolved on the way.
+            commands.entity(entity).try_remove::<Launched>();
+            continue;
+        }
+        if velocity.y <= 0.0 {
+            bursting.push((entity, transform.translation.xy()));
+        }
+    }
+    bursting.sort_by_key(|(entity, _)| *entity);
+
+    let mut count = particles.iter().count();
+    for (entity, position) in bursting {
+        commands.entity(entity).try_despawn();
+        count = count.saturating_sub(1);
+
+        let color = BURST_COLORS[rng.random_range(0..BURST_COLORS.len())];
+        let sparks = BURST_SPARKS.min(budget.0.saturating_sub(count));
+        for _ in 0..sparks {
+            let angle = rng.random_range(0.0..std::f32::consts::TAU);
+            let speed = rng.random_range(BURST_SPEED.0..BURST_SPEED.1);
+            commands.spawn((
+                Particle::new(ElementType::Spark, position),
+                LinearVelocity(Vec2::from_angle(angle) * speed),
+                Tint(color.into()),
+                ScreenWrap,
+            ));
+        }
+        count += sparks;
+    }
+}
@@ -0,0 +1,219 @@
+//! Force fields. Fans blow particles away in the direction they face, a puff
+//! of air pushes everything around it outwards, G-power pulls everything
+//! around it in, and wind pushes every loose particle in the sandbox the same
+//! way. Fields are strongest at their source and fade out towards their edge.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use std::collections::HashMap as Map;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::particle::PlacementFacing;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Wind>();
+    app.add_systems(
+        FixedUpdate,
+        (face_new_fans, blow_wind, apply_force_fields)
+            .chain()
+            .in_set(SimulationOrder::Force),
+    );
+}
+
+/// How hard wind pushes each particle while it blows.
+const WIND_FORCE: f32 = 4.0;
+
+/// How quickly the wind picks up and dies down, per second.
+const WIND_EASING: f32 = 2.0;
+
+/// Which way a fan blows, taken from the [`PlacementFacing`] when the fan is
+/// placed. Fans without one blow upwards.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct FanFacing(pub Vec2);
+
+/// The force the wind pushes every loose particle with.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct Wind(pub Vec2);
+
+/// The force field around a particle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ForceField {
+    /// How far the field reaches.
+    radius: f32,
+    /// The force right at the source. Negative forces pull instead of push.
+    strength: f32,
+    shape: FieldShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldShape {
+    /// Pushes straight away from the source, in every direction.
+    Radial,
+    /// Pushes in the direction the source faces, within this many radians
+    /// either side of it.
+    Cone(f32),
+}
+
+impl ForceField {
+    fn for_element(element_type: ElementType) -> Option<Self> {
+        match element_type {
+            ElementType::Fan => Some(Self {
+                radius: 20.0,
+                strength: 30.0,
+                shape: FieldShape::Cone(0.35),
+            }),
+            ElementType::Air => Some(Self {
+                radius: 6.0,
+                strength: 80.0,
+                shape: FieldShape::Radial,
+            }),
+            ElementType::GPower => Some(Self {
+                radius: 15.0,
+                strength: -25.0,
+                shape: FieldShape::Radial,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The force the field puts on something `offset` away from its source,
+    /// when the source faces `direction`. `None` if a cone doesn't reach that
+    /// far to the side.
+    fn force_at(&self, offset: Vec2, direction: Vec2) -> Option<Vec2> {
+        let push = match self.shape {
+            FieldShape::Radial => offset.normalize_or_zero(),
+            FieldShape::Cone(half_angle) => {
+                if offset.angle_to(direction).abs() > half_angle {
+                    return None;
+                }
+                direction
+            }
+        };
+        let falloff = (1.0 - offset.length() / self.radius).max(0.0);
+        Some(push * self.strength * falloff)
+    }
+}
+
+/// Points newly placed fans the way the [`PlacementFacing`] faces.
+fn face_new_fans(
+    mut commands: Commands,
+    facing: Res<PlacementFacing>,
+    fans: Query<(Entity, &ElementType), (Added<ElementType>, Without<FanFacing>)>,
+) {
+    for (entity, element_type) in &fans {
+        if *element_type == ElementType::Fan {
+            commands.entity(entity).try_insert(FanFacing(facing.0));
+        }
+    }
+}
+
+/// Blows the wind while there is wind in the sandbox, away from the side it
+/// was placed on, and lets it die down once the gusts are gone.
+fn blow_wind(time: Res<Time>, mut wind: ResMut<Wind>, gusts: Query<(&ElementType, &Transform)>) {
+    let gusts: Vec<f32> = gusts
+        .iter()
+        .filter(|(element_type, _)| **element_type == ElementType::Wind)
+        .map(|(_, transform)| transform.translation.x)
+        .collect();
+    let target = if gusts.is_empty() {
+        Vec2::ZERO
+    } else {
+        let side = gusts.iter().sum::<f32>() / gusts.len() as f32;
+        Vec2::X * -side.signum() * WIND_FORCE
+    };
+    let easing = (WIND_EASING * time.delta_secs()).min(1.0);
+    wind.0 = wind.0.lerp(target, easing);
+}
+
+/// Adds up the wind and every force field acting on each loose particle and
+/// applies the total for the next physics step.
+fn apply_force_fields(
+    mut commands: Commands,
+    wind: Res<Wind>,
+    spatial_query: SpatialQuery,
+    emitters: Query<(Entity, &ElementType, &Transform, Option<&FanFacing>)>,
+    bodies: Query<(Entity, &RigidBody, &Transform), With<ElementType>>,
+) {
+    let mut forces: Map<Entity, Vec2> = Map::new();
+    if wind.0 != Vec2::ZERO {
+        for (entity, body, _) in &bodies {
+            if body.is_dynamic() {
+                forces.insert(entity, wind.0);
+            }
+        }
+    }
+
+    let mut fields: Vec<(Entity, Vec2, Vec2, ForceField)> = emitters
+        .iter()
+        .filter_map(|(entity, element_type, transform, facing)| {
+            let field = ForceField::for_element(*element_type)?;
+            let direction = facing.map_or(Vec2::Y, |facing| facing.normalize_or_zero());
+            Some((entity, transform.translation.xy(), direction, field))
+        })
+        .collect();
+    fields.sort_by_key(|(entity, ..)| *entity);
+
+    for (entity, source, direction, field) in fields {
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+        let mut caught = spatial_query.shape_intersections(
+            &Collider::circle(field.radius),
+            source,
+            0.0,
+            &filter,
+        );
+        caught.sort();
+        for other in caught {
+            let Ok((_, body, transform)) = bodies.get(other) else {
+                continue;
+            };
+            if !body.is_dynamic() {
+                continue;
+            }
+            let offset = transform.translation.xy() - source;
+            if let Some(force) = field.force_at(offset, direction) {
+                *forces.entry(other).or_default() += force;
+            }
+        }
+    }
+
+    for (entity, force) in forces {
+        commands
+            .entity(entity)
+            .try_insert(ExternalForce::new(force).with_persistence(false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fans_only_blow_within_their_cone() {
+        let fan = ForceField::for_element(ElementType::Fan).unwrap();
+        let ahead = fan.force_at(Vec2::new(0.0, 10.0), Vec2::Y).unwrap();
+        assert!(ahead.y > 0.0 && ahead.x == 0.0);
+        // Just inside and just outside the 0.35 radian spread.
+        let inside = Vec2::from_angle(0.3).rotate(Vec2::new(0.0, 10.0));
+        assert!(fan.force_at(inside, Vec2::Y).is_some());
+        let outside = Vec2::from_angle(0.4).rotate(Vec2::new(0.0, 10.0));
+        assert_eq!(fan.force_at(outside, Vec2::Y), None);
+        assert_eq!(fan.force_at(Vec2::new(0.0, -10.0), Vec2::Y), None);
+    }
+
+    #[test]
+    fn fans_blow_the_way_they_face() {
+        let fan = ForceField::for_element(ElementType::Fan).unwrap();
+        assert_eq!(fan.force_at(Vec2::new(0.0, 10.0), Vec2::X), None);
+        let force = fan.force_at(Vec2::new(10.0, 0.0), Vec2::X).unwrap();
+        assert!(force.x > 0.0 && force.y == 0.0);
+    }
+
+    #[test]
+    fn force_fades_towards_the_edge_of_the_field() {
+        let fan = ForceField::for_element(ElementType::Fan).unwrap();
+        let near = fan.force_at(Vec2::new(0.0, 2.0), Vec2::Y).unwrap();
+        let far = fan.force_at(Vec2::new(0.0, 18.0), Vec2::Y).unwrap();
+        assert!(near.length() > far.length());
+    }
+}
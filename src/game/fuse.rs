@@ -0,0 +1,129 @@
+//! Fuses. Once lit, a fuse burns along its length at a steady rate, lighting
+//! whatever it touches as the flame passes and leaving ash behind, so chain
+//! reactions can be set off on a timer.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType};
+use super::heat::Temperature;
+use super::particle::{Particle, neighbours};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FuseBurnRate>();
+    app.add_systems(
+        FixedUpdate,
+        (light_fuses, burn_fuses)
+            .chain()
+            .in_set(SimulationOrder::Fuse),
+    );
+    app.add_systems(Update, show_flame);
+}
+
+/// A fuse gets lit when it, or anything touching it, is at least this hot.
+const IGNITION_TEMPERATURE: f32 = 200.0;
+
+/// How fast lit fuses burn, in particles per second.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct FuseBurnRate(pub f32);
+
+impl Default for FuseBurnRate {
+    fn default() -> Self {
+        Self(10.0)
+    }
+}
+
+impl FuseBurnRate {
+    /// How long a single fuse particle burns for.
+    fn burn_time(&self) -> f32 {
+        1.0 / self.0.max(f32::EPSILON)
+    }
+}
+
+/// A lit fuse particle, and how long until it burns out.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Burning {
+    seconds_left: f32,
+}
+
+/// Lights fuses that are hot enough or touching something hot enough.
+fn light_fuses(
+    mut commands: Commands,
+    burn_rate: Res<FuseBurnRate>,
+    spatial_query: SpatialQuery,
+    fuses: Query<(Entity, &ElementType, &Transform, &Temperature), Without<Burning>>,
+    temperatures: Query<&Temperature>,
+) {
+    for (entity, element_type, transform, temperature) in &fuses {
+        if *element_type != ElementType::Fuse {
+            continue;
+        }
+        let lit = temperature.0 >= IGNITION_TEMPERATURE
+            || neighbours(&spatial_query, entity, transform.translation.xy())
+                .into_iter()
+                .filter_map(|other| temperatures.get(other).ok())
+                .any(|other| other.0 >= IGNITION_TEMPERATURE);
+        if lit {
+            commands.entity(entity).try_insert(Burning {
+                seconds_left: burn_rate.burn_time(),
+            });
+        }
+    }
+}
+
+/// Burns lit fuses down to ash. As each particle burns out, it lights the fuse
+/// next to it and heats everything else it touches as hot as fire.
+fn burn_fuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    burn_rate: Res<FuseBurnRate>,
+    spatial_query: SpatialQuery,
+    mut burning: Query<(Entity, &Transform, &mut Burning)>,
+    mut touching: Query<(&ElementType, &mut Temperature, Has<Burning>)>,
+) {
+    let dt = time.delta_secs();
+    let mut burnt_out: Vec<(Entity, Vec2)> = Vec::new();
+    for (entity, transform, mut burning) in &mut burning {
+        burning.seconds_left -= dt;
+        if burning.seconds_left <= 0.0 {
+            burnt_out.push((entity, transform.translation.xy()));
+        }
+    }
+    burnt_out.sort_by_key(|(entity, _)| *entity);
+
+    let flame_temperature = Element::from_type(ElementType::Fire).temperature;
+    for (entity, position) in burnt_out {
+        for other in neighbours(&spatial_query, entity, position) {
+            let Ok((element_type, mut temperature, lit)) = touching.get_mut(other) else {
+                continue;
+            };
+            if *element_type == ElementType::Fuse {
+                if !lit {
+                    commands.entity(other).try_insert(Burning {
+                        seconds_left: burn_rate.burn_time(),
+                    });
+                }
+            } else {
+                temperature.0 = temperature.0.max(flame_temperature);
+            }
+        }
+        commands
+            .entity(entity)
+            .try_remove::<Burning>()
+            .try_insert(Particle::transmute(ElementType::Ash));
+    }
+}
+
+/// Colors lit fuses like fire.
+fn show_flame(
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    fuses: Query<&MeshMaterial2d<ColorMaterial>, Added<Burning>>,
+) {
+    let flame_color = Element::from_type(ElementType::Fire).color;
+    for material in &fuses {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.color = flame_color;
+        }
+    }
+}
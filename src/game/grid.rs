@@ -0,0 +1,715 @@
+//! A falling-sand simulation on a fixed grid, for scenes with far more
+//! particles than the physics engine can handle. Each cell holds at most one
+//! element, and cells move, conduct heat, change phase and react using the same
+//! [`Element`] properties and [`ReactionRegistry`] as the physics particles.
+//!
+//! The grid is split into chunks, and chunks where nothing happened last tick
+//! are skipped, so large settled piles cost next to nothing.
+//!
+//! This backend is used instead of the physics particles when the
+//! [`SimulationBackend`] is [`SimulationBackend::Grid`].
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    input::common_conditions::input_pressed,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use std::collections::HashMap as Map;
+
+use super::SimulationBackend;
+use super::elements::{DiffusionRule, Element, ElementType, Explosive, SelectedElement};
+use super::heat::{AMBIENT_TEMPERATURE, conducted_heat, cooled_to_ambient, mixed_temperature};
+use super::phase::phase_change;
+use super::reaction::{Reaction, ReactionContext, ReactionRegistry};
+use super::rng::SimulationRng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Startup,
+        spawn_grid.run_if(resource_equals(SimulationBackend::Grid)),
+    );
+    app.add_systems(FixedUpdate, step_grid.run_if(resource_exists::<CellGrid>));
+    app.add_systems(
+        Update,
+        (
+            paint_cells.run_if(input_pressed(MouseButton::Left)),
+            draw_grid,
+        )
+            .chain()
+            .run_if(resource_exists::<CellGrid>),
+    );
+}
+
+/// Width of the grid in cells. A cell is the same size as a physics particle.
+const GRID_WIDTH: usize = 640;
+/// Height of the grid in cells.
+const GRID_HEIGHT: usize = 360;
+
+/// Width and height of the square chunks the grid is split into.
+const CHUNK_SIZE: usize = 32;
+const CHUNKS_WIDE: usize = GRID_WIDTH.div_ceil(CHUNK_SIZE);
+const CHUNKS_HIGH: usize = GRID_HEIGHT.div_ceil(CHUNK_SIZE);
+
+/// Radius in cells of the brush that paints elements into the grid.
+const BRUSH_RADIUS: i32 = 2;
+
+/// How many cells a runny liquid can flow sideways in a single tick. Thicker
+/// liquids flow less far.
+const LIQUID_DISPERSION: i32 = 4;
+
+/// Cells this close to the ambient temperature are done cooling off, so the
+/// chunk they are in may sleep.
+const SETTLED_TEMPERATURE: f32 = 1.0;
+
+/// Offsets to the eight cells around a cell.
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    element_type: ElementType,
+    temperature: f32,
+    /// How many ticks in a row the cell has stayed where it is. Stands in for
+    /// how long it has been touching its neighbours.
+    resting_ticks: u32,
+    /// How long the cell has been its current element, in seconds.
+    age: f32,
+    /// How long the cell lasts as its current element, in seconds.
+    lifetime: f32,
+    /// Matches [`CellGrid::clock`] once the cell has been updated this tick, so
+    /// a cell that moves ahead of the update sweep isn't updated twice.
+    clock: bool,
+}
+
+/// The cells of the grid simulation. Cell `(0, 0)` is the bottom left corner.
+#[derive(Resource)]
+pub struct CellGrid {
+    cells: Vec<Option<Cell>>,
+    /// Chunks to update this tick.
+    awake: Vec<bool>,
+    /// Chunks to update next tick.
+    wake_next: Vec<bool>,
+    /// Element properties, looked up once per element type.
+    elements: Map<ElementType, Element>,
+    rng: SmallRng,
+    clock: bool,
+}
+
+/// The image the grid is drawn into.
+#[derive(Resource)]
+struct GridImage(Handle<Image>);
+
+fn index(x: i32, y: i32) -> Option<usize> {
+    let in_bounds = (0..GRID_WIDTH as i32).contains(&x) && (0..GRID_HEIGHT as i32).contains(&y);
+    in_bounds.then(|| y as usize * GRID_WIDTH + x as usize)
+}
+
+fn chunk(x: i32, y: i32) -> Option<usize> {
+    index(x, y).map(|_| (y as usize / CHUNK_SIZE) * CHUNKS_WIDE + x as usize / CHUNK_SIZE)
+}
+
+fn is_fluid(element: &Element) -> bool {
+    matches!(
+        element.diffusion_rule,
+        DiffusionRule::Fill | DiffusionRule::Diffuse
+    )
+}
+
+impl CellGrid {
+    fn new(rng: SmallRng) -> Self {
+        Self {
+            cells: vec![None; GRID_WIDTH * GRID_HEIGHT],
+            awake: vec![false; CHUNKS_WIDE * CHUNKS_HIGH],
+            wake_next: vec![false; CHUNKS_WIDE * CHUNKS_HIGH],
+            elements: Map::new(),
+            rng,
+            clock: false,
+        }
+    }
+
+    fn get(&self, x: i32, y: i32) -> Option<Cell> {
+        index(x, y).and_then(|index| self.cells[index])
+    }
+
+    fn is_empty(&self, x: i32, y: i32) -> bool {
+        index(x, y).is_some_and(|index| self.cells[index].is_none())
+    }
+
+    fn element(&mut self, element_type: ElementType) -> Element {
+        self.elements
+            .entry(element_type)
+            .or_insert_with(|| Element::from_type(element_type))
+            .clone()
+    }
+
+    fn color(&mut self, cell: Cell) -> [u8; 4] {
+        let element = self
+            .elements
+            .entry(cell.element_type)
+            .or_insert_with(|| Element::from_type(cell.element_type));
+        let mut color = element.color.to_srgba();
+        if element.lifetime.is_some_and(|lifetime| lifetime.fades) {
+            color.alpha *= 1.0 - (cell.age / cell.lifetime).clamp(0.0, 1.0);
+        }
+        color.to_u8_array()
+    }
+
+    /// Makes sure the chunk holding this cell, and any chunk it borders, is
+    /// updated next tick.
+    fn wake(&mut self, x: i32, y: i32) {
+        for (dx, dy) in NEIGHBOURS.iter().chain(&[(0, 0)]) {
+            if let Some(chunk) = chunk(x + dx, y + dy) {
+                self.wake_next[chunk] = true;
+            }
+        }
+    }
+
+    fn new_cell(&mut self, element_type: ElementType, temperature: f32) -> Cell {
+        let lifetime = match self.element(element_type).lifetime {
+            Some(lifetime) => lifetime.roll(&mut self.rng),
+            None => f32::INFINITY,
+        };
+        Cell {
+            element_type,
+            temperature,
+            resting_ticks: 0,
+            age: 0.0,
+            lifetime,
+            clock: self.clock,
+        }
+    }
+
+    /// Fills empty cells within the brush radius of `(x, y)` with an element.
+    fn paint(&mut self, x: i32, y: i32, element_type: ElementType) {
+        let temperature = self.element(element_type).temperature;
+        for dy in -BRUSH_RADIUS..=BRUSH_RADIUS {
+            for dx in -BRUSH_RADIUS..=BRUSH_RADIUS {
+                if dx * dx + dy * dy > BRUSH_RADIUS * BRUSH_RADIUS || !self.is_empty(x + dx, y + dy)
+                {
+                    continue;
+                }
+                let cell = self.new_cell(element_type, temperature);
+                if let Some(index) = index(x + dx, y + dy) {
+                    self.cells[index] = Some(cell);
+                    self.wake(x + dx, y + dy);
+                }
+            }
+        }
+    }
+
+    /// Advances the simulation by one tick of `dt` seconds.
+    fn step(&mut self, registry: &ReactionRegistry, dt: f32) {
+        self.clock = !self.clock;
+        std::mem::swap(&mut self.awake, &mut self.wake_next);
+        self.wake_next.fill(false);
+
+        // Sweep from the bottom up so falling cells land in rows that have
+        // already been updated. Alternate the sideways direction at random so
+        // piles don't lean to one side.
+        for y in 0..GRID_HEIGHT as i32 {
+            let leftwards = self.rng.random_bool(0.5);
+            for i in 0..GRID_WIDTH as i32 {
+                let x = if leftwards {
+                    GRID_WIDTH as i32 - 1 - i
+                } else {
+                    i
+                };
+                if chunk(x, y).is_some_and(|chunk| self.awake[chunk]) {
+                    self.update_cell(x, y, registry, dt);
+                }
+            }
+        }
+    }
+
+    fn update_cell(&mut self, x: i32, y: i32, registry: &ReactionRegistry, dt: f32) {
+        let Some(here) = index(x, y) else {
+            return;
+        };
+        let Some(mut cell) = self.cells[here] else {
+            return;
+        };
+        if cell.clock == self.clock {
+            return;
+        }
+        cell.clock = self.clock;
+        let element = self.element(cell.element_type);
+
+        // Conduct heat to the cells to the right and above. The cells to the
+        // left and below conduct heat to this one when they are updated.
+        for (dx, dy) in [(1, 0), (0, 1)] {
+            let Some(neighbour_index) = index(x + dx, y + dy) else {
+                continue;
+            };
+            let Some(mut neighbour) = self.cells[neighbour_index] else {
+                continue;
+            };
+            let neighbour_element = self.element(neighbour.element_type);
+            let heat = conducted_heat(
+                (&element, cell.temperature),
+                (&neighbour_element, neighbour.temperature),
+                dt,
+            );
+            if heat != 0.0 {
+                cell.temperature -= heat / element.thermal_mass();
+                neighbour.temperature += heat / neighbour_element.thermal_mass();
+                self.cells[neighbour_index] = Some(neighbour);
+            }
+        }
+        cell.temperature = cooled_to_ambient(&element, cell.temperature, dt);
+        if (cell.temperature - AMBIENT_TEMPERATURE).abs() > SETTLED_TEMPERATURE {
+            self.wake(x, y);
+        }
+
+        if let Some(explosive) = element.explosive {
+            let ignited = cell.temperature >= explosive.ignition_temperature
+                || NEIGHBOURS
+                    .iter()
+                    .filter_map(|(dx, dy)| self.get(x + dx, y + dy))
+                    .any(|neighbour| neighbour.temperature >= explosive.ignition_temperature);
+            if ignited {
+                self.explode(x, y, explosive);
+                return;
+            }
+        }
+
+        if let Some(into) = phase_change(&element, cell.temperature) {
+            self.cells[here] = Some(self.new_cell(into, cell.temperature));
+            self.wake(x, y);
+            return;
+        }
+
+        if let Some(lifetime) = element.lifetime {
+            cell.age += dt;
+            self.wake(x, y);
+            if cell.age >= cell.lifetime {
+                let decayed = lifetime
+                    .decays_into
+                    .map(|into| self.new_cell(into, cell.temperature));
+                self.cells[here] = decayed;
+                return;
+            }
+        }
+        self.cells[here] = Some(cell);
+
+        if self.react(x, y, cell, registry) {
+            return;
+        }
+        self.move_cell(x, y, cell, &element);
+    }
+
+    /// Blows up the explosive at `(x, y)`, turning it into fire. Nothing has
+    /// momentum on the grid, so the blast breaks what it can and sets off other
+    /// explosives, but doesn't throw anything.
+    fn explode(&mut self, x: i32, y: i32, explosive: Explosive) {
+        let reach = explosive.radius.ceil() as i32;
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                if distance > explosive.radius {
+                    continue;
+                }
+                let Some(index) = index(x + dx, y + dy) else {
+                    continue;
+                };
+                let Some(mut cell) = self.cells[index] else {
+                    continue;
+                };
+                let element = self.element(cell.element_type);
+                let strength = explosive.power * (1.0 - distance / explosive.radius);
+                if let Some(other) = element.explosive {
+                    // Heat other explosives past their ignition point so they
+                    // go off when they are next updated.
+                    cell.temperature = cell.temperature.max(other.ignition_temperature);
+                    self.cells[index] = Some(cell);
+                } else if strength > element.blast_resistance {
+                    self.cells[index] = element
+                        .shatters_into
+                        .map(|into| self.new_cell(into, cell.temperature));
+                }
+                self.wake(x + dx, y + dy);
+            }
+        }
+
+        let fire_temperature = self.element(ElementType::Fire).temperature;
+        if let Some(index) = index(x, y) {
+            self.cells[index] = Some(self.new_cell(ElementType::Fire, fire_temperature));
+        }
+    }
+
+    /// The element types of the cells around each of `cells`, not counting
+    /// `cells` themselves.
+    fn surroundings(&self, cells: &[(i32, i32)]) -> Vec<ElementType> {
+        let mut around: Vec<(i32, i32)> = cells
+            .iter()
+            .flat_map(|(x, y)| NEIGHBOURS.iter().map(move |(dx, dy)| (x + dx, y + dy)))
+            .filter(|position| !cells.contains(position))
+            .collect();
+        around.sort();
+        around.dedup();
+        around
+            .into_iter()
+            .filter_map(|(x, y)| self.get(x, y))
+            .map(|cell| cell.element_type)
+            .collect()
+    }
+
+    /// Tries the reactions this cell can take part in, either on its own or
+    /// with one of the neighbours it can react with, picked at random. Returns
+    /// whether it reacted.
+    fn react(&mut self, x: i32, y: i32, cell: Cell, registry: &ReactionRegistry) -> bool {
+        if registry
+            .find_reactions(&[cell.element_type])
+            .next()
+            .is_some()
+        {
+            // Keep the chunk awake while a reaction could still happen.
+            self.wake(x, y);
+            let context = ReactionContext {
+                temperature: cell.temperature,
+                contact_ticks: u32::MAX,
+                surroundings: self.surroundings(&[(x, y)]),
+                roll_every_tick: true,
+            };
+            let reaction = registry
+                .find_reactions(&[cell.element_type])
+                .find(|reaction| reaction.conditions.are_met(&context, &mut self.rng));
+            if let Some(reaction) = reaction {
+                self.replace_with_products(&[(x, y)], reaction);
+                return true;
+            }
+        }
+
+        let partners: Vec<(i32, i32)> = NEIGHBOURS
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|(other_x, other_y)| {
+                self.get(*other_x, *other_y).is_some_and(|other| {
+                    registry
+                        .find_reactions(&[cell.element_type, other.element_type])
+                        .next()
+                        .is_some()
+                })
+            })
+            .collect();
+        if partners.is_empty() {
+            return false;
+        }
+        // As above, keep trying for as long as the cells stay together.
+        self.wake(x, y);
+        let (other_x, other_y) = partners[self.rng.random_range(0..partners.len())];
+        let Some(other) = self.get(other_x, other_y) else {
+            return false;
+        };
+        let reactants = [cell.element_type, other.element_type];
+        // Resting ticks only say how long each cell has stayed put, not how
+        // long this pair has been touching, so there is no first tick of
+        // contact to roll on. Roll on every try instead.
+        let context = ReactionContext {
+            temperature: (cell.temperature + other.temperature) / 2.0,
+            contact_ticks: cell.resting_ticks.min(other.resting_ticks),
+            surroundings: self.surroundings(&[(x, y), (other_x, other_y)]),
+            roll_every_tick: true,
+        };
+        let reaction = registry
+            .find_reactions(&reactants)
+            .find(|reaction| reaction.conditions.are_met(&context, &mut self.rng));
+        if let Some(reaction) = reaction {
+            self.replace_with_products(&[(x, y), (other_x, other_y)], reaction);
+            return true;
+        }
+        false
+    }
+
+    /// Clears the reactant cells and fills them, and any empty cells around
+    /// them, with the products. Products that don't fit are lost. There is no
+    /// momentum on the grid, so the reaction's energy only shows up as heat.
+    fn replace_with_products(&mut self, reactants: &[(i32, i32)], reaction: &Reaction) {
+        let mut heat = Vec::with_capacity(reactants.len());
+        for (x, y) in reactants {
+            if let Some(cell) = self.get(*x, *y) {
+                let thermal_mass = self.element(cell.element_type).thermal_mass();
+                heat.push((thermal_mass, cell.temperature));
+            }
+            if let Some(index) = index(*x, *y) {
+                self.cells[index] = None;
+            }
+        }
+        let temperature = mixed_temperature(heat);
+
+        let mut slots: Vec<(i32, i32)> = reactants.to_vec();
+        for (x, y) in reactants {
+            for (dx, dy) in NEIGHBOURS {
+                let slot = (x + dx, y + dy);
+                if self.is_empty(slot.0, slot.1) && !slots.contains(&slot) {
+                    slots.push(slot);
+                }
+            }
+        }
+        for (product, (x, y)) in reaction.product_list().into_iter().zip(slots) {
+            let start_temperature = self.element(product).temperature.max(temperature);
+            let cell = self.new_cell(product, start_temperature);
+            if let Some(index) = index(x, y) {
+                self.cells[index] = Some(cell);
+            }
+        }
+        for (x, y) in reactants {
+            self.wake(*x, *y);
+        }
+    }
+
+    /// Moves a cell according to its element's diffusion rule.
+    fn move_cell(&mut self, x: i32, y: i32, cell: Cell, element: &Element) {
+        let side = if self.rng.random_bool(0.5) { 1 } else { -1 };
+        let moved = match element.diffusion_rule {
+            DiffusionRule::Frozen => false,
+            DiffusionRule::Fall => [(0, -1), (side, -1), (-side, -1)]
+                .into_iter()
+                .any(|(dx, dy)| self.try_move(x, y, dx, dy, element)),
+            DiffusionRule::Fill => {
+                [(0, -1), (side, -1), (-side, -1)]
+                    .into_iter()
+                    .any(|(dx, dy)| self.try_move(x, y, dx, dy, element))
+                    || self.flow_sideways(x, y, side, element)
+            }
+            DiffusionRule::Diffuse => {
+                // Gases lighter than air drift up, heavier ones sink.
+                let vertical = if element.density < 1.0 { 1 } else { -1 };
+                let directions = [
+                    (0, vertical),
+                    (-1, vertical),
+                    (1, vertical),
+                    (-1, 0),
+                    (1, 0),
+                ];
+                let (dx, dy) = directions[self.rng.random_range(0..directions.len())];
+                self.try_move(x, y, dx, dy, element)
+            }
+        };
+        if moved {
+            return;
+        }
+        if let Some(index) = index(x, y) {
+            self.cells[index] = Some(Cell {
+                resting_ticks: cell.resting_ticks.saturating_add(1),
+                ..cell
+            });
+        }
+    }
+
+    /// Flows a liquid as far sideways as it can go this tick.
+    fn flow_sideways(&mut self, x: i32, y: i32, side: i32, element: &Element) -> bool {
+        let dispersion = (LIQUID_DISPERSION as f32 / (1.0 + element.viscosity)).ceil() as i32;
+        let reach = (1..=dispersion)
+            .take_while(|distance| self.is_empty(x + side * distance, y))
+            .last();
+        match reach {
+            Some(distance) => self.try_move(x, y, side * distance, 0, element),
+            None => false,
+        }
+    }
+
+    /// Moves the cell at `(x, y)` by `(dx, dy)` if the target is empty, or
+    /// swaps it with the target if it is a fluid the cell would sink or float
+    /// through.
+    fn try_move(&mut self, x: i32, y: i32, dx: i32, dy: i32, element: &Element) -> bool {
+        let (Some(from), Some(to)) = (index(x, y), index(x + dx, y + dy)) else {
+            return false;
+        };
+        let Some(mut cell) = self.cells[from] else {
+            return false;
+        };
+        if let Some(target) = self.cells[to] {
+            let target_element = self.element(target.element_type);
+            let displaces = target.element_type != cell.element_type
+                && is_fluid(&target_element)
+                && match dy.signum() {
+                    -1 => target_element.density < element.density,
+                    1 => target_element.density > element.density,
+                    _ => false,
+                };
+            if !displaces {
+                return false;
+            }
+        }
+        cell.resting_ticks = 0;
+        let displaced = self.cells[to].map(|target| Cell {
+            resting_ticks: 0,
+            clock: self.clock,
+            ..target
+        });
+        self.cells[to] = Some(cell);
+        self.cells[from] = displaced;
+        self.wake(x, y);
+        self.wake(x + dx, y + dy);
+        true
+    }
+}
+
+fn spawn_grid(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: GRID_WIDTH as u32,
+            height: GRID_HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Keep cells crisp when the grid is scaled up.
+    image.sampler = ImageSampler::nearest();
+    let handle = images.add(image);
+
+    commands.spawn((
+        Name::new("Cell Grid"),
+        Sprite {
+            image: handle.clone(),
+            custom_size: Some(Vec2::new(GRID_WIDTH as f32, GRID_HEIGHT as f32)),
+            ..default()
+        },
+    ));
+    commands.insert_resource(GridImage(handle));
+    commands.insert_resource(CellGrid::new(SmallRng::from_rng(&mut rng.0)));
+}
+
+fn step_grid(time: Res<Time>, registry: Res<ReactionRegistry>, mut grid: ResMut<CellGrid>) {
+    grid.step(&registry, time.delta_secs());
+}
+
+fn paint_cells(
+    camera: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    selected_element: Res<SelectedElement>,
+    mut grid: ResMut<CellGrid>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+    // The grid is centered on the origin.
+    let x = (world_position.x + GRID_WIDTH as f32 / 2.0).floor() as i32;
+    let y = (world_position.y + GRID_HEIGHT as f32 / 2.0).floor() as i32;
+    grid.paint(x, y, selected_element.0);
+}
+
+fn draw_grid(mut grid: ResMut<CellGrid>, image: Res<GridImage>, mut images: ResMut<Assets<Image>>) {
+    if !grid.is_changed() {
+        return;
+    }
+    let Some(image) = images.get_mut(&image.0) else {
+        return;
+    };
+    let grid = grid.bypass_change_detection();
+    let data = image.data.get_or_insert_with(Vec::new);
+    data.resize(GRID_WIDTH * GRID_HEIGHT * 4, 0);
+    for y in 0..GRID_HEIGHT {
+        // Image rows go from the top down, but the grid goes from the bottom up.
+        let row = GRID_HEIGHT - 1 - y;
+        for x in 0..GRID_WIDTH {
+            let pixel = match grid.cells[y * GRID_WIDTH + x] {
+                Some(cell) => grid.color(cell),
+                None => [0, 0, 0, 0],
+            };
+            let start = (row * GRID_WIDTH + x) * 4;
+            data[start..start + 4].copy_from_slice(&pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::reaction::ReactionConditions;
+
+    fn place(grid: &mut CellGrid, x: i32, y: i32, element_type: ElementType) {
+        let temperature = grid.element(element_type).temperature;
+        let cell = grid.new_cell(element_type, temperature);
+        grid.cells[index(x, y).unwrap()] = Some(cell);
+        grid.wake(x, y);
+    }
+
+    fn element_at(grid: &CellGrid, x: i32, y: i32) -> Option<ElementType> {
+        grid.get(x, y).map(|cell| cell.element_type)
+    }
+
+    #[test]
+    fn sand_falls_to_the_floor_and_stays() {
+        let registry = ReactionRegistry::default();
+        let mut grid = CellGrid::new(SmallRng::seed_from_u64(0));
+        place(&mut grid, 10, 5, ElementType::Sand);
+
+        for _ in 0..5 {
+            grid.step(&registry, 1.0 / 64.0);
+        }
+        assert_eq!(element_at(&grid, 10, 0), Some(ElementType::Sand));
+
+        for _ in 0..5 {
+            grid.step(&registry, 1.0 / 64.0);
+        }
+        assert_eq!(element_at(&grid, 10, 0), Some(ElementType::Sand));
+        assert_eq!(grid.cells.iter().flatten().count(), 1);
+    }
+
+    #[test]
+    fn sand_sinks_through_water() {
+        let registry = ReactionRegistry::default();
+        let mut grid = CellGrid::new(SmallRng::seed_from_u64(0));
+        // A well one cell wide, so the water has nowhere to flow but up.
+        for y in 0..3 {
+            place(&mut grid, 9, y, ElementType::Wall);
+            place(&mut grid, 11, y, ElementType::Wall);
+        }
+        place(&mut grid, 10, 0, ElementType::Water);
+        place(&mut grid, 10, 1, ElementType::Sand);
+
+        grid.step(&registry, 1.0 / 64.0);
+        assert_eq!(element_at(&grid, 10, 0), Some(ElementType::Sand));
+        assert_eq!(element_at(&grid, 10, 1), Some(ElementType::Water));
+    }
+
+    #[test]
+    fn unlikely_reactions_keep_trying_between_resting_cells() {
+        let mut registry = ReactionRegistry::default();
+        registry.register_reaction(Reaction {
+            reactants: vec![ElementType::Wall, ElementType::Wall],
+            products: vec![(ElementType::Sand, 2)],
+            conditions: ReactionConditions {
+                probability: 0.1,
+                ..default()
+            },
+            ..default()
+        });
+        let mut grid = CellGrid::new(SmallRng::seed_from_u64(0));
+        place(&mut grid, 10, 0, ElementType::Wall);
+        place(&mut grid, 11, 0, ElementType::Wall);
+
+        // The walls never move, so they have long been resting by the time
+        // the roll comes up.
+        for _ in 0..500 {
+            grid.step(&registry, 1.0 / 64.0);
+        }
+        assert_eq!(element_at(&grid, 10, 0), Some(ElementType::Sand));
+        assert_eq!(element_at(&grid, 11, 0), Some(ElementType::Sand));
+    }
+}
@@ -0,0 +1,196 @@
+//! Heat carried by particles. Touching particles conduct heat between each
+//! other, and everything slowly cools off (or warms up) to the temperature of
+//! the air around it.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType};
+use super::particle::neighbours;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (conduct_heat, exchange_heat_with_ambient)
+            .chain()
+            .in_set(SimulationOrder::Heat),
+    );
+}
+
+/// The temperature of the air around the sandbox, in degrees Celsius.
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+
+/// How quickly particles exchange heat with the surrounding air, per degree of
+/// difference. Air is a poor conductor, so this is much slower than conduction
+/// between touching particles.
+const AMBIENT_CONDUCTIVITY: f32 = 0.02;
+
+/// How hot a particle is, in degrees Celsius.
+#[derive(Component, Debug, Clone, Copy, PartialEq, PartialOrd, Deref, DerefMut)]
+pub struct Temperature(pub f32);
+
+impl Default for Temperature {
+    fn default() -> Self {
+        Self(AMBIENT_TEMPERATURE)
+    }
+}
+
+impl Element {
+    /// Heat needed to warm a whole particle of this element by one degree.
+    pub fn thermal_mass(&self) -> f32 {
+        self.density * self.heat_capacity
+    }
+}
+
+/// The temperature a group of particles would settle at if they shared all of
+/// their heat, given as `(thermal mass, temperature)` pairs.
+pub fn mixed_temperature(parts: impl IntoIterator<Item = (f32, f32)>) -> f32 {
+    let (heat, thermal_mass) =
+        parts
+            .into_iter()
+            .fold((0.0, 0.0), |(heat, mass), (thermal_mass, temperature)| {
+                (heat + thermal_mass * temperature, mass + thermal_mass)
+            });
+    if thermal_mass > 0.0 {
+        heat / thermal_mass
+    } else {
+        AMBIENT_TEMPERATURE
+    }
+}
+
+/// Moves heat between every pair of touching particles. The pairs come from
+/// [`neighbours`] rather than collisions, so frozen particles next to each
+/// other conduct heat too.
+fn conduct_heat(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut particles: Query<(Entity, &Element, &Transform, &mut Temperature), With<ElementType>>,
+) {
+    let dt = time.delta_secs();
+    let mut pairs: Vec<(Entity, Entity)> = particles
+        .iter()
+        .flat_map(|(entity, _, transform, _)| {
+            neighbours(&spatial_query, entity, transform.translation.xy())
+                .into_iter()
+                .filter(move |other| *other > entity)
+                .map(move |other| (entity, other))
+        })
+        .collect();
+    pairs.sort();
+
+    for (a, b) in pairs {
+        let Ok(
+            [
+                (_, element_a, _, mut temperature_a),
+                (_, element_b, _, mut temperature_b),
+            ],
+        ) = particles.get_many_mut([a, b])
+        else {
+            continue;
+        };
+        let heat = conducted_heat(
+            (element_a, temperature_a.0),
+            (element_b, temperature_b.0),
+            dt,
+        );
+        if heat == 0.0 {
+            continue;
+        }
+        temperature_a.0 -= heat / element_a.thermal_mass();
+        temperature_b.0 += heat / element_b.thermal_mass();
+    }
+}
+
+/// Heat that flows from `a` to `b` in `dt` seconds, given each side's element
+/// and temperature. Negative if heat flows from `b` to `a`. The flow is limited
+/// so that the pair never overshoots the temperature they would settle at.
+pub fn conducted_heat(a: (&Element, f32), b: (&Element, f32), dt: f32) -> f32 {
+    let ((element_a, temperature_a), (element_b, temperature_b)) = (a, b);
+    let (mass_a, mass_b) = (element_a.thermal_mass(), element_b.thermal_mass());
+    if mass_a <= 0.0 || mass_b <= 0.0 {
+        return 0.0;
+    }
+    let (k_a, k_b) = (
+        element_a.thermal_conductivity,
+        element_b.thermal_conductivity,
+    );
+    if k_a + k_b <= 0.0 {
+        return 0.0;
+    }
+    // Heat has to pass through both materials, so the poorer conductor
+    // dominates.
+    let conductivity = 2.0 * k_a * k_b / (k_a + k_b);
+    let difference = temperature_a - temperature_b;
+    let equilibrium_heat = difference * mass_a * mass_b / (mass_a + mass_b);
+    let heat = conductivity * difference * dt;
+    if difference > 0.0 {
+        heat.min(equilibrium_heat)
+    } else {
+        heat.max(equilibrium_heat)
+    }
+}
+
+/// Pulls every particle's temperature towards [`AMBIENT_TEMPERATURE`].
+fn exchange_heat_with_ambient(time: Res<Time>, mut particles: Query<(&Element, &mut Temperature)>) {
+    let dt = time.delta_secs();
+    for (element, mut temperature) in &mut particles {
+        temperature.0 = cooled_to_ambient(element, temperature.0, dt);
+    }
+}
+
+/// The temperature of a particle after exchanging heat with the air for `dt`
+/// seconds.
+pub fn cooled_to_ambient(element: &Element, temperature: f32, dt: f32) -> f32 {
+    let thermal_mass = element.thermal_mass();
+    if thermal_mass <= 0.0 {
+        return temperature;
+    }
+    let rate = (AMBIENT_CONDUCTIVITY * dt / thermal_mass).min(1.0);
+    temperature + (AMBIENT_TEMPERATURE - temperature) * rate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temperatures of two touching particles after conducting heat for `dt`.
+    fn conduct(a: (&Element, f32), b: (&Element, f32), dt: f32) -> (f32, f32) {
+        let heat = conducted_heat(a, b, dt);
+        (
+            a.1 - heat / a.0.thermal_mass(),
+            b.1 + heat / b.0.thermal_mass(),
+        )
+    }
+
+    #[test]
+    fn conducted_heat_flows_from_hot_to_cold() {
+        let water = Element::from_type(ElementType::Water);
+        let wall = Element::from_type(ElementType::Wall);
+        let (hot, cold) = conduct((&wall, 100.0), (&water, 0.0), 0.01);
+        assert!(hot < 100.0 && cold > 0.0);
+        assert!(hot > cold);
+    }
+
+    #[test]
+    fn conducted_heat_does_not_overshoot() {
+        let water = Element::from_type(ElementType::Water);
+        let wall = Element::from_type(ElementType::Wall);
+        for (a, b) in [(100.0, 0.0), (0.0, 100.0)] {
+            let (wall_after, water_after) = conduct((&wall, a), (&water, b), 1000.0);
+            assert!((wall_after - water_after).abs() < 1e-3);
+            let settled = mixed_temperature([(wall.thermal_mass(), a), (water.thermal_mass(), b)]);
+            assert!((wall_after - settled).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn conducted_heat_needs_a_conductor() {
+        let mut insulator = Element::from_type(ElementType::Water);
+        insulator.thermal_conductivity = 0.0;
+        assert_eq!(
+            conducted_heat((&insulator, 100.0), (&insulator.clone(), 0.0), 1.0),
+            0.0
+        );
+    }
+}
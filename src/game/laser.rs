@@ -0,0 +1,257 @@
+//! Lasers. A laser fires a beam of light in the direction it faces every tick.
+//! The beam bounces off metal and mercury, bends as it passes through glass,
+//! and stops at the first other particle it hits, heating it up, or burning it
+//! away if it is flimsy enough.
+
+use avian2d::prelude::*;
+use bevy::{color::palettes::css::RED, prelude::*};
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType};
+use super::heat::Temperature;
+use super::particle::PlacementFacing;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (face_new_lasers, fire_lasers)
+            .chain()
+            .in_set(SimulationOrder::Laser),
+    );
+    app.add_systems(Update, draw_beams);
+}
+
+/// How far a beam reaches, in world units, counting every bounce.
+const LASER_RANGE: f32 = 200.0;
+
+/// The most times a beam can bounce or bend before it is cut off, so a beam
+/// caught between two mirrors doesn't bounce forever.
+const MAX_BOUNCES: usize = 32;
+
+/// Heat the beam gives the particle it hits, per tick.
+const LASER_HEAT: f32 = 30.0;
+
+/// Particles with less blast resistance than this are burnt away by the beam
+/// instead of heated.
+const CUT_RESISTANCE: f32 = 5.0;
+
+/// Elements that reflect a beam like a mirror.
+const MIRRORS: [ElementType; 2] = [ElementType::Metal, ElementType::Mercury];
+
+/// How much glass slows down light, relative to air. The higher it is, the
+/// more a beam bends going in and out of glass.
+const GLASS_INDEX: f32 = 1.5;
+
+/// How far a beam moves at a time while it passes through glass.
+const GLASS_STEP: f32 = 0.25;
+
+/// How far a beam starts away from a surface it just left, so it doesn't hit
+/// the same surface again straight away.
+const SURFACE_OFFSET: f32 = 0.01;
+
+/// Which way a laser fires, taken from the [`PlacementFacing`] when the laser
+/// is placed. Lasers without one fire upwards.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct LaserFacing(pub Vec2);
+
+/// The path a laser's beam took this tick, from the laser to where it stopped.
+#[derive(Component, Debug, Default, Clone, PartialEq, Deref, DerefMut)]
+pub struct Beam(pub Vec<Vec2>);
+
+/// Points newly placed lasers the way the [`PlacementFacing`] faces.
+fn face_new_lasers(
+    mut commands: Commands,
+    facing: Res<PlacementFacing>,
+    lasers: Query<(Entity, &ElementType), (Added<ElementType>, Without<LaserFacing>)>,
+) {
+    for (entity, element_type) in &lasers {
+        if *element_type == ElementType::Laser {
+            commands.entity(entity).try_insert(LaserFacing(facing.0));
+        }
+    }
+}
+
+/// Traces each laser's beam through the sandbox and burns whatever it ends on.
+fn fire_lasers(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    lasers: Query<(
+        Entity,
+        &ElementType,
+        &Transform,
+        Option<&LaserFacing>,
+        Has<Beam>,
+    )>,
+    mut targets: Query<(&ElementType, &Element, &mut Temperature)>,
+) {
+    let mut lasers: Vec<_> = lasers.iter().collect();
+    lasers.sort_by_key(|(entity, ..)| *entity);
+
+    for (entity, element_type, transform, facing, has_beam) in lasers {
+        if *element_type != ElementType::Laser {
+            // The laser melted or otherwise stopped being a laser.
+            if has_beam {
+                commands.entity(entity).try_remove::<Beam>();
+            }
+            continue;
+        }
+
+        let mut origin = transform.translation.xy();
+        let mut direction = facing.map_or(Vec2::Y, |facing| facing.normalize_or_zero());
+        let mut remaining = LASER_RANGE;
+        let mut beam = vec![origin];
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+        for _ in 0..MAX_BOUNCES {
+            let Ok(heading) = Dir2::new(direction) else {
+                break;
+            };
+            let Some(hit) = spatial_query.cast_ray(origin, heading, remaining, true, &filter)
+            else {
+                beam.push(origin + direction * remaining);
+                break;
+            };
+            let point = origin + direction * hit.distance;
+            beam.push(point);
+            remaining -= hit.distance;
+
+            let Ok(hit_type) = targets
+                .get(hit.entity)
+                .map(|(element_type, ..)| *element_type)
+            else {
+                break;
+            };
+            if MIRRORS.contains(&hit_type) {
+                direction = direction.reflect(hit.normal);
+                origin = point + direction * SURFACE_OFFSET;
+            } else if hit_type == ElementType::Glass {
+                let in_glass = |point: Vec2| {
+                    spatial_query
+                        .point_intersections(point, &SpatialQueryFilter::default())
+                        .into_iter()
+                        .any(|other| {
+                            targets
+                                .get(other)
+                                .is_ok_and(|(element_type, ..)| *element_type == ElementType::Glass)
+                        })
+                };
+                let (exit, exit_direction) =
+                    pass_through_glass(in_glass, point, direction, hit.normal, &mut remaining);
+                beam.push(exit);
+                origin = exit;
+                direction = exit_direction;
+            } else {
+                let Ok((_, element, mut temperature)) = targets.get_mut(hit.entity) else {
+                    break;
+                };
+                if element.blast_resistance < CUT_RESISTANCE {
+                    commands.entity(hit.entity).try_despawn();
+                } else {
+                    temperature.0 += LASER_HEAT / element.thermal_mass().max(f32::EPSILON);
+                }
+                break;
+            }
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+
+        commands.entity(entity).try_insert(Beam(beam));
+    }
+}
+
+/// Walks a beam that entered glass at `entry` through to the far side, bending
+/// it on the way in and on the way out. `in_glass` tells whether a point is
+/// inside glass. Glass particles are upright squares, so the beam leaves
+/// through either a horizontal or a vertical face, and light that meets that
+/// face at too shallow an angle bounces back inside instead.
+///
+/// Returns where the beam left the glass and the direction it left in.
+fn pass_through_glass(
+    in_glass: impl Fn(Vec2) -> bool,
+    entry: Vec2,
+    direction: Vec2,
+    normal: Vec2,
+    remaining: &mut f32,
+) -> (Vec2, Vec2) {
+    let mut direction = direction.refract(normal, 1.0 / GLASS_INDEX);
+    let mut point = entry;
+    while *remaining > 0.0 {
+        let inside = point;
+        point += direction * GLASS_STEP;
+        *remaining -= GLASS_STEP;
+        if in_glass(point) {
+            continue;
+        }
+        // The beam crossed a vertical face if stepping sideways alone takes it
+        // out of the glass, and a horizontal one otherwise.
+        let face = if in_glass(Vec2::new(point.x, inside.y)) {
+            Vec2::new(0.0, direction.y.signum())
+        } else {
+            Vec2::new(direction.x.signum(), 0.0)
+        };
+        let out = direction.refract(-face, GLASS_INDEX);
+        if out != Vec2::ZERO {
+            return (point, out);
+        }
+        // Total internal reflection: bounce off the face and carry on through
+        // the glass.
+        point = inside;
+        direction = direction.reflect(face);
+    }
+    (point, direction)
+}
+
+/// Draws every laser's beam.
+fn draw_beams(mut gizmos: Gizmos, beams: Query<&Beam>) {
+    for beam in &beams {
+        gizmos.linestrip_2d(beam.iter().copied(), RED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glass_bends_a_beam_and_lets_it_out_parallel() {
+        // A slab of glass between y = 0 and y = 10.
+        let in_glass = |point: Vec2| (0.0..10.0).contains(&point.y);
+        let direction = Vec2::new(1.0, -1.0).normalize();
+        let mut remaining = LASER_RANGE;
+        let (exit, exit_direction) = pass_through_glass(
+            in_glass,
+            Vec2::new(0.0, 10.0),
+            direction,
+            Vec2::Y,
+            &mut remaining,
+        );
+        assert!(exit.y < 0.0);
+        // Bent towards the normal inside, so it comes out short of where a
+        // straight beam would.
+        assert!(exit.x > 4.0 && exit.x < 7.0);
+        assert!(exit_direction.distance(direction) < 1e-4);
+    }
+
+    #[test]
+    fn glass_reflects_a_beam_meeting_a_face_too_shallowly() {
+        // A block of glass, entered near its right edge by a steep beam that
+        // meets the right face at far more than the critical angle.
+        let in_glass =
+            |point: Vec2| (0.0..10.0).contains(&point.x) && (0.0..10.0).contains(&point.y);
+        let direction = Vec2::new(0.3, -1.0).normalize();
+        let mut remaining = LASER_RANGE;
+        let (exit, exit_direction) = pass_through_glass(
+            in_glass,
+            Vec2::new(9.5, 10.0),
+            direction,
+            Vec2::Y,
+            &mut remaining,
+        );
+        // It bounced off the right face and left through the bottom,
+        // mirrored.
+        assert!(exit.y < 0.0);
+        assert!(exit.x > 7.0 && exit.x < 10.0);
+        let mirrored = Vec2::new(-direction.x, direction.y);
+        assert!(exit_direction.distance(mirrored) < 1e-4);
+    }
+}
@@ -1,10 +1,185 @@
 use bevy::prelude::*;
 
+pub mod bubble;
+pub mod buoyancy;
+pub mod cloner;
+pub mod cloud;
+pub mod creature;
+pub mod decay;
+pub mod diffusion;
+pub mod electricity;
 pub mod elements;
+pub mod explosion;
+pub mod firework;
+pub mod force;
+pub mod fuse;
+pub mod grid;
+pub mod heat;
+pub mod laser;
 pub mod particle;
+pub mod phase;
+pub mod plant;
+pub mod pump;
 pub mod reaction;
+pub mod rng;
 pub mod sandbox;
+pub mod torch;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins((particle::plugin, sandbox::plugin, reaction::plugin));
+    app.insert_resource(SimulationBackend::from_env());
+
+    app.add_plugins((
+        buoyancy::plugin,
+        decay::plugin,
+        diffusion::plugin,
+        grid::plugin,
+        heat::plugin,
+        particle::plugin,
+        phase::plugin,
+        sandbox::plugin,
+        reaction::plugin,
+        rng::plugin,
+    ));
+    // Elements that behave in ways of their own, beyond moving, heating and
+    // reacting.
+    app.add_plugins((
+        bubble::plugin,
+        cloner::plugin,
+        cloud::plugin,
+        creature::plugin,
+        electricity::plugin,
+        explosion::plugin,
+        firework::plugin,
+        force::plugin,
+        fuse::plugin,
+        laser::plugin,
+        plant::plugin,
+        pump::plugin,
+        torch::plugin,
+    ));
+
+    // Order new `SimulationSystems` variants by adding them here:
+    app.configure_sets(
+        FixedUpdate,
+        (
+            SimulationSystems::Move,
+            SimulationSystems::Heat,
+            SimulationSystems::React,
+            SimulationSystems::Transition,
+        )
+            .chain()
+            .run_if(resource_equals(SimulationBackend::Physics)),
+    );
+    // Order new `SimulationOrder` variants by adding them here, within the step
+    // they belong to:
+    app.configure_sets(
+        FixedUpdate,
+        (
+            (
+                SimulationOrder::Diffusion,
+                SimulationOrder::Buoyancy,
+                SimulationOrder::Creature,
+                SimulationOrder::Force,
+            )
+                .chain()
+                .in_set(SimulationSystems::Move),
+            SimulationOrder::Heat.in_set(SimulationSystems::Heat),
+            (
+                SimulationOrder::Reaction,
+                SimulationOrder::Explosion,
+                SimulationOrder::Electricity,
+                SimulationOrder::Fuse,
+                SimulationOrder::Cloner,
+                SimulationOrder::Plant,
+                SimulationOrder::Pump,
+                SimulationOrder::Cloud,
+                SimulationOrder::Bubble,
+                SimulationOrder::Laser,
+                SimulationOrder::Firework,
+                SimulationOrder::Torch,
+            )
+                .chain()
+                .in_set(SimulationSystems::React),
+            (SimulationOrder::Phase, SimulationOrder::Decay)
+                .chain()
+                .in_set(SimulationSystems::Transition),
+        ),
+    );
+}
+
+/// Which simulation drives the sandbox. This is chosen once at startup, by
+/// setting the `SANDBOX_BACKEND` environment variable to `physics` or `grid`.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationBackend {
+    /// Every particle is a rigid body in the physics engine. Particles can
+    /// tumble, bounce and be pushed around, but only a few thousand fit before
+    /// the frame rate suffers.
+    #[default]
+    Physics,
+    /// Every particle is a cell on a fixed grid, like classic falling-sand
+    /// games. Movement is coarser, but scenes can hold hundreds of thousands
+    /// of particles.
+    Grid,
+}
+
+impl SimulationBackend {
+    fn from_env() -> Self {
+        match std::env::var("SANDBOX_BACKEND").as_deref() {
+            Ok("grid") => Self::Grid,
+            Ok("physics") | Err(_) => Self::Physics,
+            Ok(other) => {
+                warn!("Unknown simulation backend {other:?}, using physics instead.");
+                Self::Physics
+            }
+        }
+    }
+}
+
+/// The steps of the particle simulation in the `FixedUpdate` schedule. When
+/// adding a new variant, make sure to order it in the `configure_sets` call
+/// above.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum SimulationSystems {
+    /// Push particles around according to how their elements move.
+    Move,
+    /// Move heat between particles and the air.
+    Heat,
+    /// Run reactions between particles.
+    React,
+    /// Change particles into other elements when they get too hot or cold.
+    Transition,
+}
+
+/// The parts of the simulation, in the order their systems run within each
+/// [`SimulationSystems`] step. Left to itself, Bevy runs the systems of a step
+/// in whatever order it likes, so systems drawing from
+/// [`SimulationRng`](rng::SimulationRng) would hand out the random numbers
+/// differently from one run to the next. When adding a new variant, make sure
+/// to order it in the `configure_sets` call above.
+///
+/// For the same reason, systems in these sets go through particles sorted by
+/// entity, rather than in query or hash map order, wherever the order changes
+/// the outcome: who draws which random number, who gets to a shared neighbour
+/// first, or whose forces and heat add up first.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum SimulationOrder {
+    Diffusion,
+    Buoyancy,
+    Creature,
+    Force,
+    Heat,
+    Reaction,
+    Explosion,
+    Electricity,
+    Fuse,
+    Cloner,
+    Plant,
+    Pump,
+    Cloud,
+    Bubble,
+    Laser,
+    Firework,
+    Torch,
+    Phase,
+    Decay,
 }
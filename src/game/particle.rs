@@ -1,7 +1,10 @@
 use avian2d::prelude::*;
 use bevy::{input::common_conditions::input_pressed, prelude::*};
 
+use super::SimulationBackend;
+use super::diffusion::Motion;
 use super::elements::{DiffusionRule, Element, ElementType, SelectedElement};
+use super::heat::Temperature;
 use super::sandbox::ScreenWrap;
 
 pub(super) fn plugin(app: &mut App) {
@@ -9,52 +12,153 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         (
-            spawn_particle_on_click.run_if(input_pressed(MouseButton::Left)),
+            spawn_particle_on_click.run_if(
+                input_pressed(MouseButton::Left).and(resource_equals(SimulationBackend::Physics)),
+            ),
             setup_particle_visuals,
+            rotate_placement_facing,
+            show_placement_facing,
         ),
     );
 
     app.insert_resource(SelectedElement(ElementType::Sand));
+    app.init_resource::<ParticleBudget>();
+    app.init_resource::<PlacementFacing>();
 }
 
+/// The most particles the sandbox holds at once. Nothing spawns new particles
+/// on its own once this many exist, so emitters can't grind the simulation to
+/// a halt.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct ParticleBudget(pub usize);
+
+impl Default for ParticleBudget {
+    fn default() -> Self {
+        Self(5000)
+    }
+}
+
+/// Which way newly placed particles of elements that point somewhere, like
+/// fans, face. The Q and E keys turn it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct PlacementFacing(pub Vec2);
+
+impl Default for PlacementFacing {
+    fn default() -> Self {
+        Self(Vec2::Y)
+    }
+}
+
+/// Elements whose particles face the [`PlacementFacing`] they were placed with.
+const DIRECTIONAL: [ElementType; 2] = [ElementType::Fan, ElementType::Laser];
+
+/// How far each press of Q or E turns the [`PlacementFacing`], in radians.
+const FACING_STEP: f32 = std::f32::consts::FRAC_PI_4;
+
+/// A color a particle is drawn in instead of its element's, like the sparks of
+/// a firework.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct Tint(pub Color);
+
 #[derive(Bundle, Debug, Clone)]
 pub struct Particle {
+    pub element_type: ElementType,
     pub element: Element,
     collider: Collider,
     rigid_body: RigidBody,
+    motion: Motion,
+    collision_events: CollisionEventsEnabled,
+    colliding_entities: CollidingEntities,
+    temperature: Temperature,
     transform: Transform,
 }
 
 impl Particle {
-    pub fn new(element: Element, position: Vec2) -> Self {
-        let (collider, rigid_body) = match element.diffusion_rule {
-            DiffusionRule::Frozen => (Collider::rectangle(1.0, 1.0), RigidBody::Static),
-            DiffusionRule::Fall => (Collider::rectangle(1.0, 1.0), RigidBody::Dynamic),
-            DiffusionRule::Fill => (Collider::circle(0.5), RigidBody::Dynamic),
-            DiffusionRule::Diffuse => (Collider::circle(0.1), RigidBody::Dynamic),
-        };
+    pub fn new(element_type: ElementType, position: Vec2) -> Self {
+        let element = Element::from_type(element_type);
+        let temperature = Temperature(element.temperature);
+        let (collider, rigid_body) = physics_body(&element);
+        let motion = Motion::for_element(&element);
         Self {
+            element_type,
             element,
             collider,
             rigid_body,
+            motion,
+            collision_events: CollisionEventsEnabled,
+            colliding_entities: CollidingEntities::default(),
+            temperature,
             transform: Transform::from_translation(position.extend(0.0)),
         }
     }
+
+    /// The components of a particle that depend on what it is made of.
+    /// Inserting these on an existing particle turns it into another element in
+    /// place, keeping its position, velocity and temperature.
+    pub fn transmute(element_type: ElementType) -> impl Bundle {
+        let element = Element::from_type(element_type);
+        let (collider, rigid_body) = physics_body(&element);
+        let motion = Motion::for_element(&element);
+        (element_type, element, collider, rigid_body, motion)
+    }
+
+    /// Starts the particle out at `temperature` instead of the element's
+    /// default.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Temperature(temperature);
+        self
+    }
 }
 
+/// How far around a particle to look for the particles touching it.
+const NEIGHBOUR_RADIUS: f32 = 0.75;
+
+/// The particles touching the one at `position`, sorted by entity. Unlike
+/// [`CollidingEntities`], this also finds frozen particles next to each other,
+/// since the physics engine never checks static bodies against each other.
+pub fn neighbours(spatial_query: &SpatialQuery, entity: Entity, position: Vec2) -> Vec<Entity> {
+    let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+    let mut neighbours = spatial_query.shape_intersections(
+        &Collider::circle(NEIGHBOUR_RADIUS),
+        position,
+        0.0,
+        &filter,
+    );
+    neighbours.sort();
+    neighbours
+}
+
+/// The shape and kind of body a particle moves with.
+fn physics_body(element: &Element) -> (Collider, RigidBody) {
+    match element.diffusion_rule {
+        DiffusionRule::Frozen => (Collider::rectangle(1.0, 1.0), RigidBody::Static),
+        // Held in place until something knocks it loose, see
+        // `diffusion::dislodge_particles`.
+        DiffusionRule::Fall if element.dislodge_impulse.is_some() => {
+            (Collider::rectangle(1.0, 1.0), RigidBody::Static)
+        }
+        DiffusionRule::Fall => (Collider::rectangle(1.0, 1.0), RigidBody::Dynamic),
+        DiffusionRule::Fill => (Collider::circle(0.5), RigidBody::Dynamic),
+        DiffusionRule::Diffuse => (Collider::circle(0.1), RigidBody::Dynamic),
+    }
+}
+
+/// Gives particles a mesh and material matching their element, both when they
+/// are spawned and when they turn into another element.
 fn setup_particle_visuals(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    query: Query<(Entity, &Element), Added<Element>>,
+    query: Query<(Entity, &Element, Option<&Tint>), Changed<Element>>,
 ) {
-    for (entity, element) in query.iter() {
+    for (entity, element, tint) in query.iter() {
         let mesh_handle = match element.diffusion_rule {
             DiffusionRule::Frozen | DiffusionRule::Fall => meshes.add(Rectangle::new(1.0, 1.0)),
             DiffusionRule::Fill => meshes.add(Circle::new(0.5)),
             DiffusionRule::Diffuse => meshes.add(Circle::new(0.1)),
         };
-        let material_handle = materials.add(ColorMaterial::from_color(element.color));
+        let color = tint.map_or(element.color, |tint| tint.0);
+        let material_handle = materials.add(ColorMaterial::from_color(color));
         commands
             .entity(entity)
             .insert((Mesh2d(mesh_handle), MeshMaterial2d(material_handle)));
@@ -66,17 +170,60 @@ fn spawn_particle_on_click(
     camera: Query<(&Camera, &GlobalTransform)>,
     windows: Query<&Window>,
     selected_element: Res<SelectedElement>,
+    budget: Res<ParticleBudget>,
+    particles: Query<(), With<ElementType>>,
 ) {
+    if particles.iter().count() >= budget.0 {
+        return;
+    }
     if let Ok(window) = windows.single() {
         if let Some(cursor_position) = window.cursor_position() {
             if let Ok((camera, camera_transform)) = camera.single() {
                 if let Ok(world_position) =
                     camera.viewport_to_world_2d(camera_transform, cursor_position)
                 {
-                    let element = Element::from_type(selected_element.0);
-                    commands.spawn((Particle::new(element, world_position), ScreenWrap));
+                    commands.spawn((
+                        Particle::new(selected_element.0, world_position),
+                        ScreenWrap,
+                    ));
                 }
             }
         }
     }
 }
+
+/// Turns the [`PlacementFacing`] anticlockwise with Q and clockwise with E.
+fn rotate_placement_facing(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut facing: ResMut<PlacementFacing>,
+) {
+    let turns =
+        keyboard.just_pressed(KeyCode::KeyQ) as i32 - keyboard.just_pressed(KeyCode::KeyE) as i32;
+    if turns != 0 {
+        facing.0 = Vec2::from_angle(turns as f32 * FACING_STEP).rotate(facing.0);
+    }
+}
+
+/// Points an arrow from the cursor in the [`PlacementFacing`] while a
+/// directional element is selected.
+fn show_placement_facing(
+    mut gizmos: Gizmos,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    windows: Query<&Window>,
+    selected_element: Res<SelectedElement>,
+    facing: Res<PlacementFacing>,
+) {
+    if !DIRECTIONAL.contains(&selected_element.0) {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.single(), camera.single()) else {
+        return;
+    };
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+    if let Ok(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) {
+        let tip = world_position + facing.normalize_or_zero() * 4.0;
+        gizmos.arrow_2d(world_position, tip, Color::WHITE);
+    }
+}
@@ -0,0 +1,60 @@
+//! Phase transitions. Particles that get hotter or colder than their element's
+//! [`PhaseChange`] temperatures turn into another element, like water freezing
+//! into ice or magma cooling into stone.
+
+use bevy::prelude::*;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType, PhaseChange};
+use super::heat::Temperature;
+use super::particle::Particle;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, change_phase.in_set(SimulationOrder::Phase));
+}
+
+fn change_phase(mut commands: Commands, particles: Query<(Entity, &Element, &Temperature)>) {
+    for (entity, element, temperature) in &particles {
+        if let Some(into) = phase_change(element, temperature.0) {
+            commands
+                .entity(entity)
+                .try_insert(Particle::transmute(into));
+        }
+    }
+}
+
+/// The element a particle at this temperature turns into, if it is past one of
+/// its element's [`PhaseChange`] temperatures.
+pub fn phase_change(element: &Element, temperature: f32) -> Option<ElementType> {
+    let cooled = element
+        .cools_into
+        .filter(|change| temperature < change.temperature);
+    let heated = element
+        .heats_into
+        .filter(|change| temperature > change.temperature);
+    cooled.or(heated).map(|PhaseChange { into, .. }| into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_change_crosses_each_threshold_in_one_direction() {
+        let water = Element::from_type(ElementType::Water);
+        assert_eq!(phase_change(&water, 101.0), Some(ElementType::Steam));
+        assert_eq!(phase_change(&water, -1.0), Some(ElementType::Ice));
+        assert_eq!(phase_change(&water, 50.0), None);
+    }
+
+    #[test]
+    fn phase_change_leaves_a_gap_between_boiling_and_condensing() {
+        let water = Element::from_type(ElementType::Water);
+        let steam = Element::from_type(ElementType::Steam);
+        // Between steam condensing at 95 and water boiling at 100, both stay as
+        // they are instead of flipping back and forth.
+        assert_eq!(phase_change(&water, 97.0), None);
+        assert_eq!(phase_change(&steam, 97.0), None);
+        assert_eq!(phase_change(&steam, 90.0), Some(ElementType::Water));
+    }
+}
@@ -0,0 +1,181 @@
+//! Plants. Seeds fall like powder, and a seed that lands on soil while
+//! touching water sprouts into a vine. Vines grow upwards and sideways from
+//! their tip for as long as they have water to drink, soaking up any water the
+//! tip touches along the way.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+
+use super::SimulationOrder;
+use super::elements::ElementType;
+use super::particle::{Particle, ParticleBudget, neighbours};
+use super::rng::SimulationRng;
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (sprout_seeds, grow_vines)
+            .chain()
+            .in_set(SimulationOrder::Plant),
+    );
+}
+
+/// Elements a seed can take root in.
+const SOIL: [ElementType; 3] = [ElementType::Sand, ElementType::Powder, ElementType::Ash];
+
+/// How many vine particles a plant grows from each water particle it drinks.
+const GROWTH_PER_WATER: u32 = 3;
+
+/// Seconds between each new vine particle.
+const GROWTH_INTERVAL: f32 = 0.25;
+
+/// Where a vine can grow its next particle, relative to its tip. Up is listed
+/// more than once so vines mostly climb.
+const GROWTH_DIRECTIONS: [Vec2; 6] = [
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(-1.0, 1.0),
+    Vec2::new(1.0, 1.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(1.0, 0.0),
+];
+
+/// The growing tip of a vine, with the water it has left to grow with.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct VineTip {
+    water: u32,
+    seconds_until_growth: f32,
+}
+
+/// Sprouts seeds that rest on soil and touch water, drinking the water.
+fn sprout_seeds(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    seeds: Query<(Entity, &ElementType, &Transform), Without<VineTip>>,
+    element_types: Query<&ElementType>,
+) {
+    let mut drunk = HashSet::new();
+    let mut seeds: Vec<(Entity, Vec2)> = seeds
+        .iter()
+        .filter(|(_, element_type, _)| **element_type == ElementType::Seed)
+        .map(|(entity, _, transform)| (entity, transform.translation.xy()))
+        .collect();
+    seeds.sort_by_key(|(entity, _)| *entity);
+
+    for (entity, position) in seeds {
+        let on_soil = spatial_query
+            .point_intersections(position - Vec2::Y, &SpatialQueryFilter::default())
+            .into_iter()
+            .filter_map(|below| element_types.get(below).ok())
+            .any(|below| SOIL.contains(below));
+        if !on_soil {
+            continue;
+        }
+        let water: Vec<Entity> = neighbours(&spatial_query, entity, position)
+            .into_iter()
+            .filter(|other| {
+                !drunk.contains(other)
+                    && element_types
+                        .get(*other)
+                        .is_ok_and(|element_type| *element_type == ElementType::Water)
+            })
+            .collect();
+        if water.is_empty() {
+            continue;
+        }
+        for other in &water {
+            commands.entity(*other).try_despawn();
+        }
+        drunk.extend(water.iter().copied());
+        commands.entity(entity).try_insert((
+            Particle::transmute(ElementType::Vine),
+            VineTip {
+                water: water.len() as u32 * GROWTH_PER_WATER,
+                seconds_until_growth: GROWTH_INTERVAL,
+            },
+        ));
+    }
+}
+
+/// Grows vines from their tips, one particle at a time, into a free spot
+/// above or beside the tip. The new particle becomes the tip and takes the
+/// rest of the water with it. Vines stop growing while the
+/// [`ParticleBudget`] is used up, and hold on to their water until it frees up.
+fn grow_vines(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rng: ResMut<SimulationRng>,
+    budget: Res<ParticleBudget>,
+    spatial_query: SpatialQuery,
+    mut tips: Query<(Entity, &ElementType, &Transform, &mut VineTip)>,
+    element_types: Query<&ElementType>,
+) {
+    let dt = time.delta_secs();
+    let mut drunk = HashSet::new();
+    let mut ready: Vec<(Entity, Vec2)> = Vec::new();
+    for (entity, element_type, transform, mut tip) in &mut tips {
+        // The tip burned, dissolved or otherwise stopped being a vine.
+        if *element_type != ElementType::Vine {
+            commands.entity(entity).try_remove::<VineTip>();
+            continue;
+        }
+        tip.seconds_until_growth -= dt;
+        if tip.seconds_until_growth <= 0.0 {
+            tip.seconds_until_growth = GROWTH_INTERVAL;
+            ready.push((entity, transform.translation.xy()));
+        }
+    }
+    ready.sort_by_key(|(entity, _)| *entity);
+
+    let mut count = element_types.iter().count();
+    for (entity, position) in ready {
+        let water: Vec<Entity> = neighbours(&spatial_query, entity, position)
+            .into_iter()
+            .filter(|other| {
+                !drunk.contains(other)
+                    && element_types
+                        .get(*other)
+                        .is_ok_and(|element_type| *element_type == ElementType::Water)
+            })
+            .collect();
+        for other in &water {
+            commands.entity(*other).try_despawn();
+        }
+        drunk.extend(water.iter().copied());
+
+        let Ok((.., mut tip)) = tips.get_mut(entity) else {
+            continue;
+        };
+        tip.water += water.len() as u32 * GROWTH_PER_WATER;
+        if tip.water == 0 || count >= budget.0 {
+            continue;
+        }
+
+        let mut directions = GROWTH_DIRECTIONS;
+        directions.shuffle(&mut rng.0);
+        let free_spot = directions
+            .into_iter()
+            .map(|direction| position + direction)
+            .find(|spot| {
+                spatial_query
+                    .point_intersections(*spot, &SpatialQueryFilter::default())
+                    .is_empty()
+            });
+        let Some(spot) = free_spot else {
+            continue;
+        };
+        commands.spawn((
+            Particle::new(ElementType::Vine, spot),
+            VineTip {
+                water: tip.water - 1,
+                seconds_until_growth: GROWTH_INTERVAL,
+            },
+            ScreenWrap,
+        ));
+        count += 1;
+        commands.entity(entity).try_remove::<VineTip>();
+    }
+}
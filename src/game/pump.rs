@@ -0,0 +1,119 @@
+//! Pumps. Pump particles that touch each other form a pipe. Liquid touching
+//! the pipe is sucked in and pushed out again at the pipe's outlet, its
+//! highest particle, so water can be lifted back to the top of a machine.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use std::collections::{HashMap as Map, HashSet};
+
+use super::SimulationOrder;
+use super::elements::{DiffusionRule, Element, ElementType};
+use super::heat::Temperature;
+use super::particle::{Particle, neighbours};
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, pump_liquids.in_set(SimulationOrder::Pump));
+}
+
+/// Where pumped liquid comes out, relative to the outlet, in order of
+/// preference.
+const OUTLET_OFFSETS: [Vec2; 5] = [
+    Vec2::new(0.0, 1.0),
+    Vec2::new(-1.0, 1.0),
+    Vec2::new(1.0, 1.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(1.0, 0.0),
+];
+
+/// Moves one liquid particle through each pipe per tick, from anywhere along
+/// the pipe to its outlet.
+fn pump_liquids(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    particles: Query<(Entity, &ElementType, &Transform)>,
+    liquids: Query<(&ElementType, &Element, &Temperature)>,
+) {
+    let pumps: Map<Entity, Vec2> = particles
+        .iter()
+        .filter(|(_, element_type, _)| **element_type == ElementType::Pump)
+        .map(|(entity, _, transform)| (entity, transform.translation.xy()))
+        .collect();
+    let is_liquid = |entity: &Entity| {
+        liquids
+            .get(*entity)
+            .is_ok_and(|(_, element, _)| element.diffusion_rule == DiffusionRule::Fill)
+    };
+
+    let mut starts: Vec<Entity> = pumps.keys().copied().collect();
+    starts.sort();
+
+    let mut visited = HashSet::new();
+    let mut pumped = HashSet::new();
+    for start in starts {
+        if !visited.insert(start) {
+            continue;
+        }
+
+        // Follow the pipe out from this pump, noting the liquid along it.
+        let mut pipe = vec![start];
+        let mut intake = Vec::new();
+        let mut next = 0;
+        while let Some(pump) = pipe.get(next).copied() {
+            next += 1;
+            for other in neighbours(&spatial_query, pump, pumps[&pump]) {
+                if pumps.contains_key(&other) {
+                    if visited.insert(other) {
+                        pipe.push(other);
+                    }
+                } else if is_liquid(&other) {
+                    intake.push(other);
+                }
+            }
+        }
+
+        // The outlet is the highest pump in the pipe. Ties go to the lowest
+        // entity so the outlet doesn't jump around.
+        let Some(outlet) = pipe
+            .iter()
+            .copied()
+            .max_by(|a, b| pumps[a].y.total_cmp(&pumps[b].y).then(b.cmp(a)))
+        else {
+            continue;
+        };
+        let outlet_position = pumps[&outlet];
+
+        // Liquid sitting at the outlet was just pumped out, so leave it be.
+        let at_outlet: HashSet<Entity> = neighbours(&spatial_query, outlet, outlet_position)
+            .into_iter()
+            .collect();
+        let Some(liquid) = intake
+            .into_iter()
+            .filter(|liquid| !at_outlet.contains(liquid) && !pumped.contains(liquid))
+            .min()
+        else {
+            continue;
+        };
+        let Some(spot) = OUTLET_OFFSETS
+            .into_iter()
+            .map(|offset| outlet_position + offset)
+            .find(|spot| {
+                spatial_query
+                    .point_intersections(*spot, &SpatialQueryFilter::default())
+                    .is_empty()
+            })
+        else {
+            continue;
+        };
+        let Ok((element_type, _, temperature)) = liquids.get(liquid) else {
+            continue;
+        };
+
+        pumped.insert(liquid);
+        commands.entity(liquid).try_despawn();
+        commands.spawn((
+            Particle::new(*element_type, spot).with_temperature(temperature.0),
+            ScreenWrap,
+        ));
+    }
+}
@@ -1,21 +1,62 @@
+use avian2d::prelude::*;
 use bevy::prelude::*;
+use rand::Rng;
 
-use super::elements::ElementType;
-use std::collections::HashMap as Map;
+use super::SimulationOrder;
+use super::elements::{DiffusionRule, Element, ElementType};
+use super::heat::{AMBIENT_TEMPERATURE, Temperature, mixed_temperature};
+use super::particle::Particle;
+use super::rng::SimulationRng;
+use super::sandbox::ScreenWrap;
+use std::collections::{HashMap as Map, HashSet};
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<ReactionRegistry>();
+    app.init_resource::<ContactTicks>();
+    app.add_systems(
+        FixedUpdate,
+        (track_contacts, react)
+            .chain()
+            .in_set(SimulationOrder::Reaction),
+    );
 }
 
+/// How many ticks a particle has to touch a virus before it is infected.
+const VIRUS_INCUBATION_TICKS: u32 = 90;
+
+/// The element that kills a virus on contact.
+const VIRUS_CURE: ElementType = ElementType::Mercury;
+
+/// How many ticks acid takes to eat through something with a corrosion
+/// resistance of 0.5. Something with a resistance of 0.8 takes four times as
+/// long, and 0.95 takes nineteen times as long.
+const ACID_TICKS: f32 = 20.0;
+
+/// How many ticks a solid has to soak in a liquid before it dissolves.
+const DISSOLVE_TICKS: u32 = 20;
+
+/// How much hotter a solution boils than the pure solvent.
+const BOILING_POINT_ELEVATION: f32 = 2.0;
+
+/// How much colder a solution freezes than the pure solvent.
+const FREEZING_POINT_DEPRESSION: f32 = 2.0;
+
 #[derive(Resource)]
 pub struct ReactionRegistry {
-    reactions: Map<(ElementType, ElementType), Reaction>,
+    reactions: Vec<Reaction>,
+    /// Indices into `reactions`, keyed by the sorted reactants so lookups
+    /// don't depend on the order the elements are given in.
+    by_reactants: Map<Vec<ElementType>, Vec<usize>>,
+    /// Indices into `reactions`, keyed by every element that takes part.
+    by_element: Map<ElementType, Vec<usize>>,
 }
 
 impl Default for ReactionRegistry {
     fn default() -> Self {
         let mut registry = Self {
-            reactions: Map::new(),
+            reactions: Vec::new(),
+            by_reactants: Map::new(),
+            by_element: Map::new(),
         };
         registry.register_reactions();
         registry
@@ -24,75 +65,1035 @@ impl Default for ReactionRegistry {
 
 impl ReactionRegistry {
     pub fn register_reaction(&mut self, reaction: Reaction) {
-        let key = if reaction.reactants.len() == 2 {
-            (reaction.reactants[0], reaction.reactants[1])
-        } else {
-            warn!(
-                "Unsupported reaction with {} reactants provided. Only binary reactions are supported.",
-                reaction.reactants.len()
-            );
-            return; // Only support binary reactions for now
-        };
-
-        // Register both permutations
-        self.reactions.insert(key, reaction);
-        self.reactions.insert(
-            (key.1, key.0),
-            Reaction {
-                reactants: vec![key.1, key.0],
-                product: self.reactions[&key].product.clone(),
-                energy_scalar: self.reactions[&key].energy_scalar,
-            },
-        );
+        if reaction.reactants.is_empty() {
+            warn!("Ignoring reaction without reactants.");
+            return;
+        }
+
+        let index = self.reactions.len();
+        let mut key = reaction.reactants.clone();
+        key.sort();
+        self.by_reactants
+            .entry(key.clone())
+            .or_default()
+            .push(index);
+        key.dedup();
+        for element in key {
+            self.by_element.entry(element).or_default().push(index);
+        }
+        self.reactions.push(reaction);
     }
 
-    pub fn find_reaction(&self, element1: ElementType, element2: ElementType) -> Option<&Reaction> {
-        self.reactions.get(&(element1, element2))
+    /// All reactions that consume exactly these reactants, in any order, in the
+    /// order they were registered.
+    pub fn find_reactions(&self, reactants: &[ElementType]) -> impl Iterator<Item = &Reaction> {
+        let mut key = reactants.to_vec();
+        key.sort();
+        self.by_reactants
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .map(|index| &self.reactions[*index])
+    }
+
+    /// All reactions that `element` takes part in, in the order they were
+    /// registered.
+    pub fn reactions_with(&self, element: ElementType) -> impl Iterator<Item = &Reaction> {
+        self.by_element
+            .get(&element)
+            .into_iter()
+            .flatten()
+            .map(|index| &self.reactions[*index])
+    }
+
+    /// Registers `solute` dissolving into `solvent` to make `solution`, and
+    /// coming back out again when the solvent boils off or freezes. The
+    /// solution boils a little hotter and freezes a little colder than the
+    /// pure solvent, into whatever the solvent turns into, so `solution`
+    /// itself shouldn't change phase when heated or cooled.
+    pub fn register_solution(
+        &mut self,
+        solute: ElementType,
+        solvent: ElementType,
+        solution: ElementType,
+    ) {
+        self.register_reaction(Reaction {
+            reactants: vec![solute, solvent],
+            products: vec![(solution, 1)],
+            energy_scalar: 1.0,
+            conditions: ReactionConditions {
+                contact_ticks: DISSOLVE_TICKS,
+                ..default()
+            },
+        });
+        if let Some(boiling) = Element::from_type(solvent).heats_into {
+            self.register_reaction(Reaction {
+                reactants: vec![solution],
+                products: vec![(boiling.into, 1), (solute, 1)],
+                energy_scalar: 1.0,
+                conditions: ReactionConditions {
+                    min_temperature: Some(boiling.temperature + BOILING_POINT_ELEVATION),
+                    ..default()
+                },
+            });
+        }
+        if let Some(freezing) = Element::from_type(solvent).cools_into {
+            self.register_reaction(Reaction {
+                reactants: vec![solution],
+                products: vec![(freezing.into, 1), (solute, 1)],
+                energy_scalar: 1.0,
+                conditions: ReactionConditions {
+                    max_temperature: Some(freezing.temperature - FREEZING_POINT_DEPRESSION),
+                    ..default()
+                },
+            });
+        }
     }
 
     fn register_reactions(&mut self) {
         // Water + Fire = Steam
         self.register_reaction(Reaction {
             reactants: vec![ElementType::Water, ElementType::Fire],
-            product: ElementType::Steam,
+            products: vec![(ElementType::Steam, 1)],
             energy_scalar: 1.2,
+            ..default()
         });
+        // Burning oil spreads the fire and gives off smoke. It doesn't catch
+        // on every touch, so flames creep through a pool instead of flashing.
         self.register_reaction(Reaction {
             reactants: vec![ElementType::Oil, ElementType::Fire],
-            product: ElementType::Fire,
+            products: vec![(ElementType::Fire, 2), (ElementType::Smoke, 1)],
             energy_scalar: 5.0,
+            conditions: ReactionConditions {
+                probability: 0.3,
+                ..default()
+            },
+        });
+        // Oil that gets hot enough catches fire on its own.
+        self.register_reaction(Reaction {
+            reactants: vec![ElementType::Oil],
+            products: vec![(ElementType::Fire, 1), (ElementType::Smoke, 1)],
+            energy_scalar: 3.0,
+            conditions: ReactionConditions {
+                min_temperature: Some(250.0),
+                ..default()
+            },
+        });
+        // A spark is too small to heat oil much, but lights it on contact.
+        self.register_reaction(Reaction {
+            reactants: vec![ElementType::Oil, ElementType::Spark],
+            products: vec![(ElementType::Fire, 2), (ElementType::Smoke, 1)],
+            energy_scalar: 3.0,
+            ..default()
         });
         self.register_reaction(Reaction {
             reactants: vec![ElementType::Powder, ElementType::Fire],
-            product: ElementType::Sand,
+            products: vec![(ElementType::Sand, 1)],
             energy_scalar: 0.8,
+            ..default()
         });
+        // Powder has to soak for a while before it clumps into sand.
         self.register_reaction(Reaction {
             reactants: vec![ElementType::Water, ElementType::Powder],
-            product: ElementType::Sand,
+            products: vec![(ElementType::Sand, 1)],
             energy_scalar: 1.0,
+            conditions: ReactionConditions {
+                contact_ticks: 30,
+                ..default()
+            },
         });
         self.register_reaction(Reaction {
             reactants: vec![ElementType::Water, ElementType::Steam],
-            product: ElementType::Water,
+            products: vec![(ElementType::Water, 1)],
             energy_scalar: 0.8,
+            ..default()
+        });
+        // Plants burn, so a garden is fuel for a fire.
+        for plant in [ElementType::Seed, ElementType::Vine] {
+            self.register_reaction(Reaction {
+                reactants: vec![plant, ElementType::Fire],
+                products: vec![(ElementType::Fire, 2), (ElementType::Smoke, 1)],
+                energy_scalar: 3.0,
+                conditions: ReactionConditions {
+                    probability: 0.4,
+                    ..default()
+                },
+            });
+            self.register_reaction(Reaction {
+                reactants: vec![plant],
+                products: vec![(ElementType::Fire, 1), (ElementType::Smoke, 1)],
+                energy_scalar: 2.0,
+                conditions: ReactionConditions {
+                    min_temperature: Some(300.0),
+                    ..default()
+                },
+            });
+        }
+        // Creatures burn up in fire and drown if they stay in water.
+        for creature in [ElementType::Ant, ElementType::Bird] {
+            self.register_reaction(Reaction {
+                reactants: vec![creature, ElementType::Fire],
+                products: vec![(ElementType::Fire, 1), (ElementType::Ash, 1)],
+                energy_scalar: 1.5,
+                ..default()
+            });
+            self.register_reaction(Reaction {
+                reactants: vec![creature, ElementType::Water],
+                products: vec![(ElementType::Water, 1)],
+                energy_scalar: 1.0,
+                conditions: ReactionConditions {
+                    contact_ticks: 60,
+                    ..default()
+                },
+            });
+        }
+        for igniter in [ElementType::Fire, ElementType::Spark] {
+            self.register_reaction(Reaction {
+                reactants: vec![ElementType::Gas, igniter],
+                products: vec![(ElementType::Fire, 2)],
+                energy_scalar: 4.0,
+                ..default()
+            });
+        }
+        self.register_solution(
+            ElementType::Salt,
+            ElementType::Water,
+            ElementType::SaltWater,
+        );
+        // Soap foams up water into bubbles a little at a time, and isn't used
+        // up doing so.
+        self.register_reaction(Reaction {
+            reactants: vec![ElementType::Soapy, ElementType::Water],
+            products: vec![(ElementType::Soapy, 1), (ElementType::Bubble, 1)],
+            energy_scalar: 1.0,
+            conditions: ReactionConditions {
+                probability: 0.05,
+                ..default()
+            },
+        });
+        // Magma sets fire to anything that burns as it flows over it, and
+        // keeps on flowing.
+        for fuel in [
+            ElementType::Oil,
+            ElementType::Gas,
+            ElementType::Seed,
+            ElementType::Vine,
+        ] {
+            self.register_reaction(Reaction {
+                reactants: vec![ElementType::Magma, fuel],
+                products: vec![(ElementType::Magma, 1), (ElementType::Fire, 1)],
+                energy_scalar: 2.0,
+                ..default()
+            });
+        }
+        // A virus takes over anything that stays in contact with it for long
+        // enough, except gases, walls, clones and its cure.
+        for &element_type in ElementType::ALL {
+            let immune = matches!(
+                element_type,
+                ElementType::Virus | ElementType::Wall | ElementType::Clone | VIRUS_CURE
+            ) || Element::from_type(element_type).diffusion_rule
+                == DiffusionRule::Diffuse;
+            if immune {
+                continue;
+            }
+            self.register_reaction(Reaction {
+                reactants: vec![ElementType::Virus, element_type],
+                products: vec![(ElementType::Virus, 2)],
+                energy_scalar: 1.0,
+                conditions: ReactionConditions {
+                    contact_ticks: VIRUS_INCUBATION_TICKS,
+                    ..default()
+                },
+            });
+        }
+        self.register_reaction(Reaction {
+            reactants: vec![ElementType::Virus, VIRUS_CURE],
+            products: vec![(VIRUS_CURE, 1)],
+            energy_scalar: 1.0,
+            ..default()
         });
+        self.register_reaction(Reaction {
+            reactants: vec![ElementType::Virus, ElementType::Fire],
+            products: vec![(ElementType::Fire, 1), (ElementType::Smoke, 1)],
+            energy_scalar: 1.5,
+            ..default()
+        });
+        // Acid eats through anything that doesn't fully resist it, taking
+        // longer the tougher the material is, and is used up doing so.
+        for &element_type in ElementType::ALL {
+            let resistance = Element::from_type(element_type).corrosion_resistance;
+            if element_type == ElementType::Acid || resistance >= 1.0 {
+                continue;
+            }
+            self.register_reaction(Reaction {
+                reactants: vec![ElementType::Acid, element_type],
+                products: vec![(ElementType::Gas, 1)],
+                energy_scalar: 1.0,
+                conditions: ReactionConditions {
+                    contact_ticks: (ACID_TICKS * resistance / (1.0 - resistance)).round() as u32,
+                    ..default()
+                },
+            });
+        }
     }
 }
 
 /// Represents the type of effect a reaction can have. For all reactions, the reactant particles are consumed and replaced by one or more new product particles. The effect determines how the reaction behaves in terms of energy and momentum conservation.
+///
+/// A reaction can have any number of reactants. Reactions with a single
+/// reactant happen on their own, like decay, while reactions with more need
+/// all of their reactants to be touching.
 pub struct Reaction {
     pub reactants: Vec<ElementType>,
-    pub product: ElementType,
+    /// The elements that replace the reactants, with how many of each.
+    pub products: Vec<(ElementType, usize)>,
     pub energy_scalar: f32, // The total energy of the system (momentum, heat, etc.) is scaled and applied to the product particles evenly distributed per unit mass. If intensity is between 0 and 1, the reaction is endothermic (absorbs energy). If intensity is greater than 1, the reaction is exothermic (releases energy). Must be greater than 0.
+    pub conditions: ReactionConditions,
 }
 
 impl Default for Reaction {
     fn default() -> Self {
         Self {
             reactants: vec![],
-            product: ElementType::Powder,
+            products: vec![],
             energy_scalar: 1.0,
+            conditions: ReactionConditions::default(),
         }
     }
 }
+
+/// Requirements that must all hold for a reaction to happen. By default a
+/// reaction happens as soon as its reactants touch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReactionConditions {
+    /// Chance of the reaction happening each time its reactants come into
+    /// contact, between 0 and 1. It is rolled once, when the reactants have
+    /// been touching for `contact_ticks`, so a contact that doesn't react
+    /// stays unreacted until they separate and touch again. Contexts that set
+    /// [`ReactionContext::roll_every_tick`] roll on every tick instead.
+    pub probability: f32,
+    /// The reactants' average temperature must be at least this hot.
+    pub min_temperature: Option<f32>,
+    /// The reactants' average temperature must be at most this hot.
+    pub max_temperature: Option<f32>,
+    /// How many ticks in a row the reactants must have been touching. Ignored
+    /// by reactions with a single reactant.
+    pub contact_ticks: u32,
+    /// An element that must be touching one of the reactants. The catalyst is
+    /// not consumed by the reaction.
+    pub catalyst: Option<ElementType>,
+}
+
+impl Default for ReactionConditions {
+    fn default() -> Self {
+        Self {
+            probability: 1.0,
+            min_temperature: None,
+            max_temperature: None,
+            contact_ticks: 0,
+            catalyst: None,
+        }
+    }
+}
+
+/// What the reactants of a possible reaction look like right now.
+pub struct ReactionContext {
+    /// Average temperature of the reactants.
+    pub temperature: f32,
+    /// How many ticks in a row the reactants have been touching.
+    pub contact_ticks: u32,
+    /// Elements touching the reactants that aren't reactants themselves.
+    pub surroundings: Vec<ElementType>,
+    /// Roll the probability on every tick rather than once per contact. Set
+    /// for single reactants, which have no contact to count, and on the grid,
+    /// where a cell only tries one neighbour at a time.
+    pub roll_every_tick: bool,
+}
+
+impl ReactionConditions {
+    /// Checks the conditions against `context`. The probability is rolled last,
+    /// and only if it could fail, so conditions that aren't met don't use up
+    /// random numbers.
+    pub fn are_met(&self, context: &ReactionContext, rng: &mut impl Rng) -> bool {
+        if self
+            .min_temperature
+            .is_some_and(|min| context.temperature < min)
+            || self
+                .max_temperature
+                .is_some_and(|max| context.temperature > max)
+        {
+            return false;
+        }
+        if context.contact_ticks < self.contact_ticks {
+            return false;
+        }
+        if self
+            .catalyst
+            .is_some_and(|catalyst| !context.surroundings.contains(&catalyst))
+        {
+            return false;
+        }
+        if self.probability >= 1.0 {
+            return true;
+        }
+        // Contacts that already had their roll don't get another one.
+        let first_chance = self.contact_ticks.max(1);
+        if !context.roll_every_tick && context.contact_ticks > first_chance {
+            return false;
+        }
+        rng.random_bool(self.probability.max(0.0) as f64)
+    }
+}
+
+/// Energy carried by every unit of mass at rest, standing in for the chemical
+/// energy stored in the reactants. Without it, two resting particles would have
+/// no energy for an exothermic reaction to scale up.
+const REST_ENERGY_PER_MASS: f32 = 50.0;
+
+/// Distance between the products of a single reaction when they are spawned,
+/// so they don't start out overlapping.
+const PRODUCT_SPACING: f32 = 0.5;
+
+/// One of `count` directions evenly spread around a circle, starting straight up.
+fn burst_direction(index: usize, count: usize) -> Vec2 {
+    Vec2::from_angle(std::f32::consts::TAU * index as f32 / count as f32).rotate(Vec2::Y)
+}
+
+impl Reaction {
+    /// Every product particle this reaction spawns, with duplicates for counts.
+    pub fn product_list(&self) -> Vec<ElementType> {
+        self.products
+            .iter()
+            .flat_map(|(element, count)| std::iter::repeat_n(*element, *count))
+            .collect()
+    }
+
+    /// The reactants that are still needed once `present` are accounted for,
+    /// or `None` if `present` can't all take part in this reaction.
+    fn missing_reactants(&self, present: &[ElementType]) -> Option<Vec<ElementType>> {
+        let mut missing = self.reactants.clone();
+        for element in present {
+            let index = missing.iter().position(|reactant| reactant == element)?;
+            missing.swap_remove(index);
+        }
+        Some(missing)
+    }
+
+    /// Scales the total energy of the reactants by [`Self::energy_scalar`] and
+    /// hands it to the products as velocity.
+    ///
+    /// `reactants` are `(mass, velocity)` pairs and `product_masses` lists the
+    /// mass of each product, in the order they will be spawned. Only the change
+    /// in energy turns into motion: a scalar of 1 keeps the reactants' kinetic
+    /// energy as it is, a higher one releases some of their rest energy on top
+    /// and a lower one takes kinetic energy away. The products inherit the
+    /// reactants' momentum, and any kinetic energy left over goes into a burst
+    /// away from the point of reaction, balanced so it adds no momentum. If
+    /// there is less energy than the inherited momentum needs, the products
+    /// are slowed down instead.
+    ///
+    /// Returns the products' velocities along with the energy they couldn't
+    /// take as motion. That only happens with a single product, which has
+    /// nothing to burst away from.
+    pub fn product_velocities(
+        &self,
+        reactants: &[(f32, Vec2)],
+        product_masses: &[f32],
+    ) -> (Vec<Vec2>, f32) {
+        let mass_in: f32 = reactants.iter().map(|(mass, _)| mass).sum();
+        let momentum: Vec2 = reactants
+            .iter()
+            .map(|(mass, velocity)| *velocity * *mass)
+            .sum();
+        let kinetic_in: f32 = reactants
+            .iter()
+            .map(|(mass, velocity)| 0.5 * mass * velocity.length_squared())
+            .sum();
+        let released = REST_ENERGY_PER_MASS * mass_in * (self.energy_scalar - 1.0);
+        let kinetic_out = (kinetic_in * self.energy_scalar + released).max(0.0);
+
+        let mass_out: f32 = product_masses.iter().sum();
+        if mass_out <= 0.0 {
+            return (vec![Vec2::ZERO; product_masses.len()], 0.0);
+        }
+
+        let drift = momentum / mass_out;
+        let drift_energy = 0.5 * mass_out * drift.length_squared();
+        if kinetic_out <= drift_energy {
+            let slowdown = if drift_energy > 0.0 {
+                (kinetic_out / drift_energy).sqrt()
+            } else {
+                0.0
+            };
+            return (vec![drift * slowdown; product_masses.len()], 0.0);
+        }
+
+        // Fan the products out evenly around a circle so the burst is
+        // symmetric and deterministic. Heavier products would carry more than
+        // their share of momentum, so the circle is shifted to keep the
+        // products' center of mass moving with the drift.
+        let count = product_masses.len();
+        let directions: Vec<Vec2> = (0..count).map(|i| burst_direction(i, count)).collect();
+        let center = directions
+            .iter()
+            .zip(product_masses)
+            .map(|(direction, mass)| *direction * *mass)
+            .sum::<Vec2>()
+            / mass_out;
+        let spread: f32 = directions
+            .iter()
+            .zip(product_masses)
+            .map(|(direction, mass)| mass * (*direction - center).length_squared())
+            .sum();
+        if spread <= 0.0 {
+            return (vec![drift; count], kinetic_out - drift_energy);
+        }
+        let burst_speed = (2.0 * (kinetic_out - drift_energy) / spread).sqrt();
+        let velocities = directions
+            .into_iter()
+            .map(|direction| drift + (direction - center) * burst_speed)
+            .collect();
+        (velocities, 0.0)
+    }
+}
+
+/// How many fixed ticks in a row each pair of touching particles has been in
+/// contact, keyed by the pair sorted by entity.
+#[derive(Resource, Debug, Default)]
+pub struct ContactTicks(Map<(Entity, Entity), u32>);
+
+/// Counts how long touching particles have been in contact. Pairs that stopped
+/// touching, or were despawned, are forgotten.
+fn track_contacts(
+    mut contacts: ResMut<ContactTicks>,
+    particles: Query<(Entity, &CollidingEntities), With<ElementType>>,
+) {
+    let mut touching = Map::new();
+    for (entity, colliding) in &particles {
+        for other in colliding.iter() {
+            if *other > entity && particles.contains(*other) {
+                let ticks = contacts.0.get(&(entity, *other)).copied().unwrap_or(0);
+                touching.insert((entity, *other), ticks + 1);
+            }
+        }
+    }
+    contacts.0 = touching;
+}
+
+type ReactantQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static ElementType,
+        &'static Element,
+        &'static Transform,
+        Option<&'static LinearVelocity>,
+        Option<&'static CollidingEntities>,
+        Option<&'static Temperature>,
+    ),
+>;
+
+/// Runs reactions between particles. Every pair of touching particles is looked
+/// up in the [`ReactionRegistry`]. Reactions that need more reactants pull them
+/// in from anything else touching either particle. Reactions with a single
+/// reactant are checked for every particle.
+fn react(
+    mut commands: Commands,
+    contacts: Res<ContactTicks>,
+    registry: Res<ReactionRegistry>,
+    mut rng: ResMut<SimulationRng>,
+    particles: ReactantQuery,
+) {
+    // Hash map iteration order changes from run to run, so sort the pairs to
+    // make the same scene produce the same cascade every time.
+    let mut pairs: Vec<((Entity, Entity), u32)> = contacts
+        .0
+        .iter()
+        .map(|(pair, ticks)| (*pair, *ticks))
+        .collect();
+    pairs.sort();
+
+    // A particle can only take part in one reaction per tick.
+    let mut consumed = HashSet::new();
+    for ((a, b), ticks) in pairs {
+        if consumed.contains(&a) || consumed.contains(&b) {
+            continue;
+        }
+        let (Ok((_, type_a, .., touching_a, _)), Ok((_, type_b, .., touching_b, _))) =
+            (particles.get(a), particles.get(b))
+        else {
+            continue;
+        };
+
+        // Anything else touching either particle could be a third reactant
+        // or a catalyst.
+        let mut neighbours: Vec<Entity> = touching_a
+            .into_iter()
+            .chain(touching_b)
+            .flat_map(|touching| touching.iter().copied())
+            .filter(|entity| *entity != a && *entity != b && !consumed.contains(entity))
+            .collect();
+        neighbours.sort();
+        neighbours.dedup();
+
+        for reaction in registry.reactions_with(*type_a) {
+            if reaction.reactants.len() < 2 {
+                continue;
+            }
+            let Some(mut missing) = reaction.missing_reactants(&[*type_a, *type_b]) else {
+                continue;
+            };
+            let mut participants = vec![a, b];
+            let mut surroundings = Vec::new();
+            for neighbour in &neighbours {
+                let Ok((_, element_type, ..)) = particles.get(*neighbour) else {
+                    continue;
+                };
+                if let Some(index) = missing.iter().position(|m| m == element_type) {
+                    missing.swap_remove(index);
+                    participants.push(*neighbour);
+                } else {
+                    surroundings.push(*element_type);
+                }
+            }
+            if !missing.is_empty() {
+                continue;
+            }
+
+            let context = ReactionContext {
+                temperature: average_temperature(&participants, &particles),
+                contact_ticks: ticks,
+                surroundings,
+                roll_every_tick: false,
+            };
+            if reaction.conditions.are_met(&context, &mut rng.0) {
+                consumed.extend(participants.iter().copied());
+                run_reaction(&mut commands, reaction, &participants, &particles);
+                break;
+            }
+        }
+    }
+
+    // Reactions with a single reactant don't need a collision to happen.
+    let mut loners: Vec<(Entity, ElementType)> = particles
+        .iter()
+        .filter(|(entity, ..)| !consumed.contains(entity))
+        .map(|(entity, element_type, ..)| (entity, *element_type))
+        .collect();
+    loners.sort();
+    for (entity, element_type) in loners {
+        if registry.find_reactions(&[element_type]).next().is_none() {
+            continue;
+        }
+        let Ok((.., touching, temperature)) = particles.get(entity) else {
+            continue;
+        };
+        let context = ReactionContext {
+            temperature: temperature.map_or(AMBIENT_TEMPERATURE, |t| t.0),
+            contact_ticks: u32::MAX,
+            surroundings: touching
+                .into_iter()
+                .flat_map(|touching| touching.iter())
+                .filter_map(|other| particles.get(*other).ok())
+                .map(|(_, element_type, ..)| *element_type)
+                .collect(),
+            roll_every_tick: true,
+        };
+        if let Some(reaction) = registry
+            .find_reactions(&[element_type])
+            .find(|reaction| reaction.conditions.are_met(&context, &mut rng.0))
+        {
+            run_reaction(&mut commands, reaction, &[entity], &particles);
+        }
+    }
+}
+
+fn average_temperature(participants: &[Entity], particles: &ReactantQuery) -> f32 {
+    let total: f32 = participants
+        .iter()
+        .filter_map(|entity| particles.get(*entity).ok())
+        .map(|(.., temperature)| temperature.map_or(AMBIENT_TEMPERATURE, |t| t.0))
+        .sum();
+    total / participants.len() as f32
+}
+
+/// Consumes the `participants` and spawns the products of `reaction` around
+/// where they were.
+fn run_reaction(
+    commands: &mut Commands,
+    reaction: &Reaction,
+    participants: &[Entity],
+    particles: &ReactantQuery,
+) {
+    let mut reactants = Vec::with_capacity(participants.len());
+    let mut heat = Vec::with_capacity(participants.len());
+    let mut center = Vec2::ZERO;
+    for (_, _, element, transform, velocity, _, temperature) in participants
+        .iter()
+        .filter_map(|entity| particles.get(*entity).ok())
+    {
+        reactants.push((element.density, velocity.map_or(Vec2::ZERO, |v| v.0)));
+        heat.push((
+            element.thermal_mass(),
+            temperature.map_or(AMBIENT_TEMPERATURE, |t| t.0),
+        ));
+        center += transform.translation.xy();
+    }
+    // The products share the reactants' heat, but are never colder than they
+    // would normally start out, so fire is always hot.
+    let temperature = mixed_temperature(heat);
+    center /= participants.len() as f32;
+    for entity in participants {
+        commands.entity(*entity).despawn();
+    }
+
+    let products = reaction.product_list();
+    let elements: Vec<Element> = products
+        .iter()
+        .map(|product| Element::from_type(*product))
+        .collect();
+    let masses: Vec<f32> = elements.iter().map(|element| element.density).collect();
+    // Whatever energy the products can't take as motion warms them instead.
+    let (velocities, spare) = reaction.product_velocities(&reactants, &masses);
+    let thermal_mass: f32 = elements.iter().map(Element::thermal_mass).sum();
+    let temperature = if thermal_mass > 0.0 {
+        temperature + spare / thermal_mass
+    } else {
+        temperature
+    };
+    for (i, (product, velocity)) in products.iter().zip(velocities).enumerate() {
+        let offset = if products.len() > 1 {
+            burst_direction(i, products.len()) * PRODUCT_SPACING
+        } else {
+            Vec2::ZERO
+        };
+        let particle = Particle::new(*product, center + offset);
+        let start_temperature = particle.element.temperature.max(temperature);
+        commands.spawn((
+            particle.with_temperature(start_temperature),
+            LinearVelocity(velocity),
+            ScreenWrap,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn empty_registry() -> ReactionRegistry {
+        ReactionRegistry {
+            reactions: Vec::new(),
+            by_reactants: Map::new(),
+            by_element: Map::new(),
+        }
+    }
+
+    fn reaction(reactants: &[ElementType]) -> Reaction {
+        Reaction {
+            reactants: reactants.to_vec(),
+            products: vec![(ElementType::Steam, 1)],
+            ..default()
+        }
+    }
+
+    fn kinetic_energy(parts: &[(f32, Vec2)]) -> f32 {
+        parts
+            .iter()
+            .map(|(mass, velocity)| 0.5 * mass * velocity.length_squared())
+            .sum()
+    }
+
+    fn momentum(parts: &[(f32, Vec2)]) -> Vec2 {
+        parts.iter().map(|(mass, velocity)| *velocity * *mass).sum()
+    }
+
+    #[test]
+    fn product_velocities_conserve_momentum() {
+        let reactants: [(f32, Vec2); 2] = [(1.0, Vec2::new(2.0, 0.0)), (2.0, Vec2::new(0.0, -1.0))];
+        let splits: [Vec<f32>; 3] = [vec![3.0], vec![1.0, 2.0], vec![0.5, 1.0, 1.5]];
+        // Reactions that lose more energy than the drift needs slow the products
+        // down instead, so only check the ones that don't.
+        for energy_scalar in [1.0, 3.0] {
+            let reaction = Reaction {
+                energy_scalar,
+                ..default()
+            };
+            for product_masses in &splits {
+                let (velocities, _) = reaction.product_velocities(&reactants, product_masses);
+                let products: Vec<(f32, Vec2)> =
+                    product_masses.iter().copied().zip(velocities).collect();
+                assert!(
+                    momentum(&products).abs_diff_eq(momentum(&reactants), 1e-4),
+                    "{products:?} at {energy_scalar}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn product_velocities_add_no_motion_without_energy_change() {
+        let reaction = Reaction::default();
+        let at_rest = [(1.0, Vec2::ZERO), (1.0, Vec2::ZERO)];
+        assert_eq!(
+            reaction.product_velocities(&at_rest, &[1.0, 1.0]),
+            (vec![Vec2::ZERO, Vec2::ZERO], 0.0)
+        );
+
+        let reactants: [(f32, Vec2); 2] = [(1.0, Vec2::new(3.0, 0.0)), (1.0, Vec2::new(-1.0, 2.0))];
+        let (velocities, spare) = reaction.product_velocities(&reactants, &[1.0, 1.0]);
+        let products: Vec<(f32, Vec2)> = velocities.into_iter().map(|v| (1.0, v)).collect();
+        assert!((kinetic_energy(&products) - kinetic_energy(&reactants)).abs() < 1e-3);
+        assert_eq!(spare, 0.0);
+    }
+
+    #[test]
+    fn product_velocities_hand_back_what_a_single_product_cannot_carry() {
+        let reaction = Reaction {
+            energy_scalar: 2.0,
+            ..default()
+        };
+        let reactants: [(f32, Vec2); 2] = [(1.0, Vec2::X), (1.0, -Vec2::X)];
+        let (velocities, spare) = reaction.product_velocities(&reactants, &[2.0]);
+        assert_eq!(velocities, vec![Vec2::ZERO]);
+        let released = REST_ENERGY_PER_MASS * 2.0 + 2.0 * kinetic_energy(&reactants);
+        assert!((spare - released).abs() < 1e-3);
+    }
+
+    #[test]
+    fn find_reactions_ignores_reactant_order() {
+        let mut registry = empty_registry();
+        registry.register_reaction(reaction(&[ElementType::Water, ElementType::Fire]));
+
+        let found = |reactants: &[ElementType]| registry.find_reactions(reactants).count();
+        assert_eq!(found(&[ElementType::Water, ElementType::Fire]), 1);
+        assert_eq!(found(&[ElementType::Fire, ElementType::Water]), 1);
+    }
+
+    #[test]
+    fn find_reactions_counts_duplicate_reactants() {
+        let mut registry = empty_registry();
+        registry.register_reaction(reaction(&[
+            ElementType::Water,
+            ElementType::Fire,
+            ElementType::Water,
+        ]));
+
+        let found = |reactants: &[ElementType]| registry.find_reactions(reactants).count();
+        assert_eq!(
+            found(&[ElementType::Fire, ElementType::Water, ElementType::Water]),
+            1
+        );
+        assert_eq!(found(&[ElementType::Water, ElementType::Fire]), 0);
+        assert_eq!(
+            found(&[ElementType::Fire, ElementType::Fire, ElementType::Water]),
+            0
+        );
+        // Each element is listed once, however many times it takes part.
+        assert_eq!(registry.reactions_with(ElementType::Water).count(), 1);
+    }
+
+    #[test]
+    fn missing_reactants_accounts_for_each_present_element_once() {
+        let reaction = reaction(&[ElementType::Water, ElementType::Water, ElementType::Fire]);
+        let missing = |present: &[ElementType]| {
+            reaction.missing_reactants(present).map(|mut missing| {
+                missing.sort();
+                missing
+            })
+        };
+
+        let mut expected = vec![ElementType::Water, ElementType::Fire];
+        expected.sort();
+        assert_eq!(missing(&[ElementType::Water]), Some(expected));
+        assert_eq!(
+            missing(&[ElementType::Fire, ElementType::Water]),
+            Some(vec![ElementType::Water])
+        );
+        assert_eq!(
+            missing(&[ElementType::Water, ElementType::Fire, ElementType::Water]),
+            Some(vec![])
+        );
+        assert_eq!(
+            missing(&[ElementType::Water, ElementType::Water, ElementType::Water]),
+            None
+        );
+        assert_eq!(missing(&[ElementType::Sand]), None);
+    }
+
+    #[test]
+    fn register_solution_dissolves_and_separates_again() {
+        let mut registry = empty_registry();
+        registry.register_solution(
+            ElementType::Salt,
+            ElementType::Water,
+            ElementType::SaltWater,
+        );
+
+        let dissolve: Vec<_> = registry
+            .find_reactions(&[ElementType::Water, ElementType::Salt])
+            .collect();
+        assert_eq!(dissolve.len(), 1);
+        assert_eq!(dissolve[0].products, vec![(ElementType::SaltWater, 1)]);
+        assert_eq!(dissolve[0].conditions.contact_ticks, DISSOLVE_TICKS);
+
+        let separate: Vec<_> = registry.find_reactions(&[ElementType::SaltWater]).collect();
+        assert_eq!(separate.len(), 2);
+        let boil = separate
+            .iter()
+            .find(|reaction| reaction.conditions.min_temperature.is_some())
+            .unwrap();
+        assert_eq!(
+            boil.products,
+            vec![(ElementType::Steam, 1), (ElementType::Salt, 1)]
+        );
+        assert_eq!(boil.conditions.min_temperature, Some(102.0));
+        let freeze = separate
+            .iter()
+            .find(|reaction| reaction.conditions.max_temperature.is_some())
+            .unwrap();
+        assert_eq!(
+            freeze.products,
+            vec![(ElementType::Ice, 1), (ElementType::Salt, 1)]
+        );
+        assert_eq!(freeze.conditions.max_temperature, Some(-2.0));
+    }
+
+    #[test]
+    fn register_solution_skips_phases_the_solvent_does_not_have() {
+        let mut registry = empty_registry();
+        // Oil neither boils nor freezes, so whatever dissolves in it stays.
+        registry.register_solution(ElementType::Salt, ElementType::Oil, ElementType::SaltWater);
+        assert_eq!(
+            registry
+                .find_reactions(&[ElementType::Oil, ElementType::Salt])
+                .count(),
+            1
+        );
+        assert_eq!(
+            registry.find_reactions(&[ElementType::SaltWater]).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn acid_takes_longer_on_tougher_materials() {
+        let registry = ReactionRegistry::default();
+        let acid_ticks = |element_type| {
+            registry
+                .find_reactions(&[ElementType::Acid, element_type])
+                .next()
+                .map(|reaction| reaction.conditions.contact_ticks)
+        };
+        let powder = acid_ticks(ElementType::Powder).unwrap();
+        let sand = acid_ticks(ElementType::Sand).unwrap();
+        let oil = acid_ticks(ElementType::Oil).unwrap();
+        assert_eq!(powder, 0);
+        assert!(powder < sand && sand < oil);
+        assert_eq!(acid_ticks(ElementType::Wall), None);
+    }
+
+    fn context(contact_ticks: u32) -> ReactionContext {
+        ReactionContext {
+            temperature: AMBIENT_TEMPERATURE,
+            contact_ticks,
+            surroundings: Vec::new(),
+            roll_every_tick: false,
+        }
+    }
+
+    #[test]
+    fn default_conditions_are_always_met() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions::default();
+        for ticks in [1, 2, 100, u32::MAX] {
+            assert!(conditions.are_met(&context(ticks), &mut rng));
+        }
+    }
+
+    #[test]
+    fn temperature_conditions_bound_the_average_temperature() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions {
+            min_temperature: Some(100.0),
+            max_temperature: Some(200.0),
+            ..default()
+        };
+        let at = |temperature| ReactionContext {
+            temperature,
+            ..context(1)
+        };
+        assert!(!conditions.are_met(&at(99.0), &mut rng));
+        assert!(conditions.are_met(&at(100.0), &mut rng));
+        assert!(conditions.are_met(&at(200.0), &mut rng));
+        assert!(!conditions.are_met(&at(201.0), &mut rng));
+    }
+
+    #[test]
+    fn contact_ticks_condition_waits_for_sustained_contact() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions {
+            contact_ticks: 30,
+            ..default()
+        };
+        assert!(!conditions.are_met(&context(29), &mut rng));
+        assert!(conditions.are_met(&context(30), &mut rng));
+        assert!(conditions.are_met(&context(31), &mut rng));
+    }
+
+    #[test]
+    fn catalyst_condition_needs_the_catalyst_nearby() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions {
+            catalyst: Some(ElementType::Wall),
+            ..default()
+        };
+        let surrounded_by = |surroundings: Vec<ElementType>| ReactionContext {
+            surroundings,
+            ..context(1)
+        };
+        assert!(!conditions.are_met(&surrounded_by(vec![]), &mut rng));
+        assert!(!conditions.are_met(&surrounded_by(vec![ElementType::Sand]), &mut rng));
+        assert!(conditions.are_met(
+            &surrounded_by(vec![ElementType::Sand, ElementType::Wall]),
+            &mut rng
+        ));
+    }
+
+    #[test]
+    fn probability_is_rolled_once_per_contact() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions {
+            probability: 0.5,
+            contact_ticks: 3,
+            ..default()
+        };
+        for ticks in [0, 2, 4, 100] {
+            assert!(!conditions.are_met(&context(ticks), &mut rng));
+        }
+        let reacted = (0..1000)
+            .filter(|_| conditions.are_met(&context(3), &mut rng))
+            .count();
+        assert!((400..600).contains(&reacted));
+    }
+
+    #[test]
+    fn probability_is_rolled_every_tick_when_asked() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let conditions = ReactionConditions {
+            probability: 0.5,
+            ..default()
+        };
+        let every_tick = ReactionContext {
+            roll_every_tick: true,
+            ..context(100)
+        };
+        let reacted = (0..1000)
+            .filter(|_| conditions.are_met(&every_tick, &mut rng))
+            .count();
+        assert!((400..600).contains(&reacted));
+    }
+}
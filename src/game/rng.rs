@@ -0,0 +1,23 @@
+//! Randomness for the simulation.
+
+use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SimulationRng>();
+}
+
+/// The seed the simulation starts from. Keeping it fixed means the same scene
+/// always plays out the same way.
+const SIMULATION_SEED: u64 = 6;
+
+/// The random number generator every random choice in the simulation should
+/// draw from, so that results are reproducible.
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimulationRng(pub StdRng);
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(SIMULATION_SEED))
+    }
+}
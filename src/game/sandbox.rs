@@ -57,9 +57,10 @@ fn apply_physics_boundary(
         commands.entity(entity).despawn();
     }
 
-    let size = window.size();
-    let half_width = size.x;
-    let half_height = size.y / 4.0;
+    let Vec2 {
+        x: half_width,
+        y: half_height,
+    } = half_extents(&window);
 
     // Top wall
     if boundary.top {
@@ -111,6 +112,13 @@ fn apply_physics_boundary(
     }
 }
 
+/// Half the width and height of the box the walls are put around, centered on
+/// the origin.
+pub fn half_extents(window: &Window) -> Vec2 {
+    let size = window.size();
+    Vec2::new(size.x, size.y / 4.0)
+}
+
 fn apply_screen_wrap(
     window: Single<&Window, With<PrimaryWindow>>,
     mut wrap_query: Query<&mut Transform, With<ScreenWrap>>,
@@ -0,0 +1,61 @@
+//! Torches. A torch is a flame that never goes out. It stays as hot as it was
+//! lit however much heat it gives away, so it sets fire to whatever it touches,
+//! and it keeps putting out fire above itself.
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::SimulationOrder;
+use super::elements::{Element, ElementType};
+use super::heat::Temperature;
+use super::particle::{Particle, ParticleBudget};
+use super::sandbox::ScreenWrap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, tend_torches.in_set(SimulationOrder::Torch));
+}
+
+/// Seconds between each flame a torch gives off.
+const FLAME_INTERVAL: f32 = 0.1;
+
+/// Keeps torches at full heat, and spawns fire on top of them while the
+/// [`ParticleBudget`] allows.
+fn tend_torches(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut seconds_until_flame: Local<f32>,
+    budget: Res<ParticleBudget>,
+    spatial_query: SpatialQuery,
+    mut torches: Query<(Entity, &ElementType, &Element, &Transform, &mut Temperature)>,
+    particles: Query<(), With<ElementType>>,
+) {
+    let mut flames: Vec<(Entity, Vec2)> = Vec::new();
+    for (entity, element_type, element, transform, mut temperature) in &mut torches {
+        if *element_type != ElementType::Torch {
+            continue;
+        }
+        temperature.0 = temperature.0.max(element.temperature);
+        flames.push((entity, transform.translation.xy() + Vec2::Y));
+    }
+
+    *seconds_until_flame -= time.delta_secs();
+    if *seconds_until_flame > 0.0 {
+        return;
+    }
+    *seconds_until_flame = FLAME_INTERVAL;
+
+    flames.sort_by_key(|(entity, _)| *entity);
+    let mut count = particles.iter().count();
+    for (_, spot) in flames {
+        if count >= budget.0 {
+            return;
+        }
+        let free = spatial_query
+            .point_intersections(spot, &SpatialQueryFilter::default())
+            .is_empty();
+        if free {
+            commands.spawn((Particle::new(ElementType::Fire, spot), ScreenWrap));
+            count += 1;
+        }
+    }
+}
@@ -256,6 +256,38 @@ fn element_type_from_button_name(name: &str) -> Option<ElementType> {
         "FIRE" => Some(ElementType::Fire),
         "STEAM" => Some(ElementType::Steam),
         "WALL" => Some(ElementType::Wall),
+        "ICE" => Some(ElementType::Ice),
+        "MAGMA" => Some(ElementType::Magma),
+        "STONE" => Some(ElementType::Stone),
+        "GLASS" => Some(ElementType::Glass),
+        "C-4" => Some(ElementType::C4),
+        "BOMB" => Some(ElementType::Bomb),
+        "NITRO" => Some(ElementType::Nitro),
+        "SPARK" => Some(ElementType::Spark),
+        "METAL" => Some(ElementType::Metal),
+        "MERCURY" => Some(ElementType::Mercury),
+        "THUNDER" => Some(ElementType::Thunder),
+        "FUSE" => Some(ElementType::Fuse),
+        "ACID" => Some(ElementType::Acid),
+        "GAS" => Some(ElementType::Gas),
+        "VIRUS" => Some(ElementType::Virus),
+        "CLONE" => Some(ElementType::Clone),
+        "SEED" => Some(ElementType::Seed),
+        "VINE" => Some(ElementType::Vine),
+        "ANT" => Some(ElementType::Ant),
+        "BIRD" => Some(ElementType::Bird),
+        "FAN" => Some(ElementType::Fan),
+        "WIND" => Some(ElementType::Wind),
+        "AIR" => Some(ElementType::Air),
+        "G-POWER" => Some(ElementType::GPower),
+        "PUMP" => Some(ElementType::Pump),
+        "CLOUD" => Some(ElementType::Cloud),
+        "LASER" => Some(ElementType::Laser),
+        "SALT" => Some(ElementType::Salt),
+        "SOAPY" => Some(ElementType::Soapy),
+        "BUBBLE" => Some(ElementType::Bubble),
+        "TORCH" => Some(ElementType::Torch),
+        "F-WORKS" => Some(ElementType::Firework),
         _ => None,
     }
 }